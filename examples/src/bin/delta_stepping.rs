@@ -0,0 +1,66 @@
+use routrs::concurrency::DELTA_STEPPING_MIN_NODES;
+use routrs::highways::GEOGRAPH as highways;
+use routrs::maritime::GEOGRAPH as maritime;
+use routrs::prelude::*;
+use routrs::railways::GEOGRAPH as railways;
+
+use std::env;
+use std::process;
+use std::time::{Duration, Instant};
+
+fn main() {
+    // Parse command line arguments
+    let args: Vec<String> = env::args().collect();
+    let num_queries: usize = match args.get(1).unwrap_or(&"20".to_string()).parse() {
+        Ok(num) => num,
+        Err(_) => {
+            eprintln!("Warning: 'num_queries' must be an integer");
+            process::exit(1);
+        }
+    };
+
+    println!(
+        "{} threads, {} queries, delta-stepping picked from {} nodes",
+        rayon::current_num_threads(),
+        num_queries,
+        DELTA_STEPPING_MIN_NODES
+    );
+    // Geographs on each side of the threshold
+    for geograph in [&*maritime, &*railways, &*highways] {
+        let queries = spread_queries(geograph, num_queries);
+        println!(
+            "\n{}: {} nodes, auto search picks {}",
+            geograph.name,
+            geograph.len(),
+            if geograph.len() >= DELTA_STEPPING_MIN_NODES && rayon::current_num_threads() > 1 {
+                "delta-stepping"
+            } else {
+                "Dijkstra"
+            }
+        );
+        for search in [Search::Dijkstra, Search::DeltaStepping] {
+            let options = RouteOptions::new().with_search(search);
+            let mut total_time = Duration::ZERO;
+            for (from, to) in &queries {
+                let start = Instant::now();
+                geograph.route(from, to, &options);
+                total_time += start.elapsed();
+            }
+            println!(
+                "{:?}\ttotal: {:?}\tavg: {:?}",
+                search,
+                total_time,
+                total_time.div_f64(queries.len() as f64)
+            );
+        }
+    }
+}
+
+/// Long queries, between nodes spread over the geograph.
+fn spread_queries(geograph: &Geograph, num_queries: usize) -> Vec<(Geoloc, Geoloc)> {
+    let nodes: Vec<Geoloc> = geograph.iter_nodes().map(|node| node.geoloc()).collect();
+    let step = (nodes.len() / (num_queries + 1)).max(1);
+    (0..num_queries)
+        .map(|i| (nodes[i * step], nodes[nodes.len() - 1 - i * step]))
+        .collect()
+}
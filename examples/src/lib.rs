@@ -20,7 +20,7 @@ pub fn calculate_distances_from_first_node(geograph: &Geograph) {
             avg_time,
             first_node.geoloc(),
             node.geoloc(),
-            distance,
+            distance.km(),
             path.len(),
             path_type
         );
@@ -12,7 +12,7 @@ fn it_calculates_highway_distance() {
     let to: Geoloc = (31.05287995, 121.2232226); // Songjiang District, Shanghai, China
     let (distance, path, path_type) = highways::shortest_path(&from, &to);
 
-    assert_eq!(distance, 57.237115955889074);
+    assert_eq!(distance.km(), 57.237115955889074);
     assert_eq!(path.len(), 39);
     assert_eq!(path_type, PathType::ViaWaypoints);
 }
@@ -1,5 +1,6 @@
-use routrs::maritime;
+use routrs::maritime::{self, Passage, PassageOptions, PassageTolls};
 use routrs::prelude::*;
+use std::time::Duration;
 
 #[test]
 fn it_reads_maritime_geograph() {
@@ -12,7 +13,107 @@ fn it_calculates_maritime_distance() {
     let to: Geoloc = (41.0067858, 28.9732219); // TRIST
     let (distance, path, path_type) = maritime::shortest_path(&from, &to);
 
-    assert_eq!(distance, 9224.95741604269);
+    assert_eq!(distance.km(), 9224.95741604269);
     assert_eq!(path.len(), 118);
     assert_eq!(path_type, PathType::ViaWaypoints);
 }
+
+#[test]
+fn it_reports_maritime_passages() {
+    let from: Geoloc = (40.6759, -74.0504); // USNYC
+    let to: Geoloc = (41.0067858, 28.9732219); // TRIST
+    let route = maritime::route(&from, &to, &RouteOptions::default());
+
+    assert_eq!(
+        maritime::passages(&route),
+        vec![Passage::Gibraltar, Passage::Dardanelles, Passage::Bosporus]
+    );
+}
+
+#[test]
+fn it_avoids_maritime_passages() {
+    let from: Geoloc = (51.95, 4.05); // NLRTM
+    let to: Geoloc = (31.2, 121.9); // CNSHA
+
+    let options = RouteOptions::new().avoid_northern_sea_route();
+    let via_suez = maritime::route(&from, &to, &options);
+    let around_suez = maritime::route(&from, &to, &options.clone().avoid_suez());
+
+    assert!(maritime::passages(&via_suez).contains(&Passage::Suez));
+    assert!(!maritime::passages(&around_suez).contains(&Passage::Suez));
+    assert!(!maritime::passages(&around_suez).contains(&Passage::NorthernSeaRoute));
+    assert!(around_suez.distance > via_suez.distance);
+}
+
+#[test]
+fn it_keeps_capesize_vessels_out_of_suez() {
+    let from: Geoloc = (51.95, 4.05); // NLRTM
+    let to: Geoloc = (31.2, 121.9); // CNSHA
+    let capesize = Vessel {
+        class: Some(SizeClass::Capesize),
+        ..Default::default()
+    };
+
+    let options = RouteOptions::new().avoid_northern_sea_route();
+    let route = maritime::route(&from, &to, &options.for_vessel(capesize));
+
+    assert!(!maritime::passages(&route).contains(&Passage::Suez));
+    assert!(!maritime::passages(&route).contains(&Passage::NorthernSeaRoute));
+}
+
+#[test]
+fn it_uses_the_northern_sea_route_in_season() {
+    let from: Geoloc = (51.95, 4.05); // NLRTM
+    let to: Geoloc = (31.2, 121.9); // CNSHA
+    let route = |date: &str| {
+        let options = RouteOptions::new().departing_on(date.parse().unwrap());
+        maritime::route(&from, &to, &options)
+    };
+
+    let summer = route("2024-08-15");
+    let winter = route("2024-01-15");
+
+    assert!(maritime::passages(&summer).contains(&Passage::NorthernSeaRoute));
+    assert!(!maritime::passages(&winter).contains(&Passage::NorthernSeaRoute));
+    assert!(winter.distance > summer.distance);
+}
+
+#[test]
+fn it_charges_passage_tolls_once() {
+    let from: Geoloc = (31.2, 121.9); // CNSHA
+    let to: Geoloc = (51.95, 4.05); // NLRTM
+    let tariff = Tariff::new(0.0, 0.0).with_toll(Passage::NorthernSeaRoute, 1.0);
+    let options = RouteOptions::new()
+        .departing_on("2024-08-15".parse().unwrap())
+        .with_tariff(&tariff);
+    let route = maritime::route(&from, &to, &options);
+
+    assert!(maritime::passages(&route).contains(&Passage::NorthernSeaRoute));
+    assert_eq!(route.price, Some(1.0));
+}
+
+#[test]
+fn it_reaches_arctic_ports_out_of_season() {
+    let from: Geoloc = (51.95, 4.05); // NLRTM
+    let to: Geoloc = (69.65, 18.95); // NOTOS
+    let options = RouteOptions::new().departing_on("2024-01-15".parse().unwrap());
+    let route = maritime::route(&from, &to, &options);
+
+    assert_eq!(route.path_type, PathType::ViaWaypoints);
+    assert!(!maritime::passages(&route).contains(&Passage::NorthernSeaRoute));
+}
+
+#[test]
+fn it_estimates_maritime_durations() {
+    let from: Geoloc = (40.6759, -74.0504); // USNYC
+    let to: Geoloc = (45.6, 13.75); // ITTRS
+    let dwell = Duration::from_secs(24 * 3_600);
+    let options = RouteOptions::new().with_dwell(dwell);
+
+    let route = maritime::route(&from, &to, &options);
+    let hours = route.duration.unwrap().as_secs_f64() / 3_600.0;
+    assert!((hours - route.distance.nautical_miles() / 14.0).abs() < 1e-6);
+
+    let via_gibraltar = maritime::route_through(&[from, (36.1, -5.35), to], &options);
+    assert!(via_gibraltar.duration.unwrap() > route.duration.unwrap() + dwell);
+}
@@ -0,0 +1,22 @@
+use routrs::maritime::{self, PORT_TAG};
+use routrs::multimodal::Multimodal;
+use routrs::prelude::*;
+use routrs::railways::{self, TERMINAL_TAG};
+
+#[test]
+fn it_tags_ports_and_rail_terminals() {
+    assert!(maritime::geograph().iter_tagged_nodes(PORT_TAG).count() > 20);
+    assert!(railways::geograph().iter_tagged_nodes(TERMINAL_TAG).count() > 20);
+}
+
+#[test]
+fn it_routes_freight_across_modes() {
+    let from: Geoloc = (48.8566, 2.3522); // Paris, France
+    let to: Geoloc = (40.7128, -74.0060); // New York, USA
+    let route = Multimodal::freight(100.0).route(&from, &to);
+
+    assert_eq!(route.path_type, PathType::ViaWaypoints);
+    assert_eq!(route.modes(), vec!["highways", "maritime", "highways"]);
+    assert!(route.cost > route.legs[1].route.cost + 2.0 * 100.0);
+    assert!(route.distance.km() > 5_500.0);
+}
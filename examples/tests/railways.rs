@@ -12,7 +12,17 @@ fn it_calculates_railway_distance() {
     let to: Geoloc = (43.3032, 5.3842); // Gare de Marseille-Saint-Charles, Marseille, France
     let (distance, path, path_type) = railways::shortest_path(&from, &to);
 
-    assert_eq!(distance, 749.4744344461568);
+    assert_eq!(distance.km(), 749.4744344461568);
     assert_eq!(path.len(), 603);
     assert_eq!(path_type, PathType::ViaWaypoints);
 }
+
+#[test]
+fn it_calculates_railway_distance_only() {
+    let from: Geoloc = (48.8768, 2.3592); // Gare de l'Est, Paris, France
+    let to: Geoloc = (43.3032, 5.3842); // Gare de Marseille-Saint-Charles, Marseille, France
+    let (distance, path_type) = railways::distance_only(&from, &to);
+
+    assert!((distance.km() - 749.4744344461568).abs() < 1e-6);
+    assert_eq!(path_type, PathType::ViaWaypoints);
+}
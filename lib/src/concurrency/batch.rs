@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use super::Leg;
+use crate::geograph::route::Query;
+use crate::geograph::{
+    Geograph, Geoloc, Geolocalizable, NodeId, Route, RouteOptions, ShortestPath,
+};
+
+/// Ids of the nodes closest to the origin and destination of a leg.
+type Endpoints = (NodeId, NodeId);
+
+/// Bit patterns of a geolocation, to find the legs sharing an endpoint.
+type LocationKey = (u64, u64);
+
+/// Calculates the shortest paths of a batch of legs with as few searches as
+/// possible: the endpoints are snapped to their closest nodes first, then the
+/// legs are grouped by the node their origin is snapped to, and each group is
+/// served by a single search, whose paths are scattered back to the legs.
+///
+/// On undirected geographs, `symmetric` batches also serve the legs going
+/// both ways between two nodes with a single path, reversed as needed.
+pub(crate) fn plan_distances<T: Geolocalizable + Send + Sync>(
+    geograph: &Geograph,
+    legs: &[Leg<T>],
+    symmetric: bool,
+) -> Vec<ShortestPath> {
+    let options = RouteOptions::default();
+    let query = Query::new(geograph, &options);
+    let snapped = snap_legs(geograph, legs, &query);
+    let searches = plan_searches(&snapped, symmetric && geograph.is_undirected());
+
+    let paths: HashMap<Endpoints, Option<Vec<(NodeId, f64)>>> = searches
+        .into_par_iter()
+        .flat_map_iter(|(origin, destinations)| {
+            let paths = geograph.dijkstra_to_many(origin, &destinations, &query);
+            destinations
+                .into_iter()
+                .map(move |destination| (origin, destination))
+                .zip(paths)
+        })
+        .collect();
+
+    legs.par_iter()
+        .zip(snapped)
+        .map(|(leg, endpoints)| {
+            let (origin, destination) = (leg.origin().geoloc(), leg.destination().geoloc());
+            let labels = endpoints.and_then(|(entry, exit)| match paths.get(&(entry, exit)) {
+                Some(labels) => labels.clone(),
+                // Searched from the other end, on undirected geographs
+                None => paths
+                    .get(&(exit, entry))?
+                    .as_ref()
+                    .map(|labels| labels.iter().rev().copied().collect()),
+            });
+            match labels {
+                Some(labels) => geograph
+                    .route_via(&origin, &destination, labels, 0.0, &query)
+                    .into(),
+                None => Route::direct(origin, destination).into(),
+            }
+        })
+        .collect()
+}
+
+/// Snaps the endpoints of the legs to their closest nodes, once for each
+/// distinct geolocation, or `None` for the legs which can't be snapped.
+fn snap_legs<T: Geolocalizable + Send + Sync>(
+    geograph: &Geograph,
+    legs: &[Leg<T>],
+    query: &Query,
+) -> Vec<Option<Endpoints>> {
+    let key = |loc: &T| {
+        let (lat, lng) = loc.geoloc();
+        (lat.to_bits(), lng.to_bits())
+    };
+    let locations: HashMap<LocationKey, Geoloc> = legs
+        .iter()
+        .flat_map(|leg| [leg.origin(), leg.destination()])
+        .map(|loc| (key(loc), loc.geoloc()))
+        .collect();
+    let closest: HashMap<LocationKey, NodeId> = locations
+        .into_par_iter()
+        .filter_map(|(key, loc)| {
+            let node = geograph.closest_where(&loc, |node| query.allows_node(node))?;
+            Some((key, node.id))
+        })
+        .collect();
+
+    legs.iter()
+        .map(|leg| {
+            let entry = closest.get(&key(leg.origin()))?;
+            let exit = closest.get(&key(leg.destination()))?;
+            Some((*entry, *exit))
+        })
+        .collect()
+}
+
+/// Groups the distinct pairs of snapped endpoints into searches, from a node
+/// to several others. When `undirected`, each pair is searched only once,
+/// from the endpoint shared by more pairs.
+fn plan_searches(snapped: &[Option<Endpoints>], undirected: bool) -> HashMap<NodeId, Vec<NodeId>> {
+    let mut pairs: Vec<Endpoints> = snapped
+        .iter()
+        .flatten()
+        .map(|&(entry, exit)| {
+            if undirected {
+                (entry.min(exit), entry.max(exit))
+            } else {
+                (entry, exit)
+            }
+        })
+        .collect();
+    pairs.sort_unstable();
+    pairs.dedup();
+
+    let mut shared: HashMap<NodeId, usize> = HashMap::new();
+    if undirected {
+        for &(a, b) in &pairs {
+            *shared.entry(a).or_default() += 1;
+            *shared.entry(b).or_default() += 1;
+        }
+    }
+    let mut searches: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    for (entry, exit) in pairs {
+        let (origin, destination) = if shared.get(&exit) > shared.get(&entry) {
+            (exit, entry)
+        } else {
+            (entry, exit)
+        };
+        searches.entry(origin).or_default().push(destination);
+    }
+    searches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geograph::{EdgeAttributes, Node, PathType};
+
+    fn geograph_fixture() -> Geograph {
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (0.0, 0.0), vec![1]))
+            .add(Node::new(1, (1.0, 1.0), vec![0, 2, 3, 4]))
+            .add(Node::new(2, (2.0, 2.0), vec![1, 3, 5]))
+            .add(Node::new(3, (3.0, 3.0), vec![1, 2, 4, 5]))
+            .add(Node::new(4, (4.0, 4.0), vec![1, 3, 5]))
+            .add(Node::new(5, (5.0, 5.0), vec![2, 3, 4]))
+            .add(Node::new(6, (-5.0, -5.0), vec![]));
+        geograph
+    }
+
+    /// Legs between every pair of a few places, both ways and repeated.
+    fn matrix_fixture() -> Vec<Leg<Geoloc>> {
+        let places = [(0.1, 0.0), (2.0, 2.1), (4.9, 5.0), (3.0, 3.0), (-5.0, -5.0)];
+        let mut legs = Vec::new();
+        for _ in 0..2 {
+            for from in places {
+                for to in places {
+                    legs.push(Leg((from, to)));
+                }
+            }
+        }
+        legs
+    }
+
+    #[test]
+    fn it_matches_the_shortest_paths() {
+        let geograph = geograph_fixture();
+        let legs = matrix_fixture();
+
+        let planned = plan_distances(&geograph, &legs, false);
+        assert_eq!(planned.len(), legs.len());
+        for (leg, (distance, path, path_type)) in legs.iter().zip(planned) {
+            let (expected, expected_path, expected_type) =
+                geograph.shortest_path(leg.origin(), leg.destination());
+            assert_eq!(distance.km(), expected.km());
+            assert_eq!(*path, *expected_path);
+            assert_eq!(path_type, expected_type);
+        }
+    }
+
+    #[test]
+    fn it_exploits_symmetry() {
+        let geograph = geograph_fixture();
+        let legs = matrix_fixture();
+        assert!(geograph.is_undirected());
+
+        let symmetric = plan_distances(&geograph, &legs, true);
+        for (leg, (distance, path, path_type)) in legs.iter().zip(symmetric) {
+            let (expected, _, expected_type) =
+                geograph.shortest_path(leg.origin(), leg.destination());
+            assert!((distance.km() - expected.km()).abs() < 1e-9);
+            assert_eq!(path[0], leg.origin().geoloc());
+            assert_eq!(path_type, expected_type);
+        }
+        assert_eq!(
+            plan_distances(&geograph, &[Leg(((0.1, 0.0), (-5.0, -5.0)))], true)[0].2,
+            PathType::Direct
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_direction_of_edge_geometries() {
+        let mut geograph = geograph_fixture();
+        let geometry = vec![(1.0, 2.0)];
+        geograph.add_edge(
+            1,
+            2,
+            EdgeAttributes {
+                geometry: Some(geometry.clone()),
+                ..Default::default()
+            },
+        );
+        assert!(!geograph.is_undirected());
+        let legs = [Leg(((0.1, 0.0), (2.0, 2.1))), Leg(((2.0, 2.1), (0.1, 0.0)))];
+
+        let symmetric = plan_distances(&geograph, &legs, true);
+        for (leg, (distance, path, _)) in legs.iter().zip(symmetric) {
+            let (expected, expected_path, _) =
+                geograph.shortest_path(leg.origin(), leg.destination());
+            assert_eq!(distance.km(), expected.km());
+            assert_eq!(*path, *expected_path);
+        }
+
+        // Mirrored on the edge going back
+        geograph.add_edge(
+            2,
+            1,
+            EdgeAttributes {
+                geometry: Some(geometry),
+                ..Default::default()
+            },
+        );
+        assert!(geograph.is_undirected());
+
+        // Reset by the nodes added since
+        geograph.add(Node::new(9, (9.0, 9.0), vec![1]));
+        assert!(!geograph.is_undirected());
+    }
+
+    #[test]
+    fn it_plans_one_search_per_origin() {
+        let snapped = [Some((0, 5)), Some((0, 3)), Some((0, 5)), Some((5, 0)), None];
+
+        let searches = plan_searches(&snapped, false);
+        assert_eq!(searches.len(), 2);
+        assert_eq!(searches[&0], vec![3, 5]);
+        assert_eq!(searches[&5], vec![0]);
+
+        let searches = plan_searches(&snapped, true);
+        assert_eq!(searches.len(), 1);
+        assert_eq!(searches[&0], vec![3, 5]);
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use rayon::prelude::*;
+
+use crate::geograph::budget::SearchResult;
+use crate::geograph::route::Query;
+use crate::geograph::{Geograph, NodeId};
+
+/// Minimum number of nodes of a geograph for `Search::Auto` to pick
+/// delta-stepping: on smaller ones, Dijkstra's algorithm is faster. The
+/// `delta_stepping` example times both searches on geographs on each side.
+pub const DELTA_STEPPING_MIN_NODES: usize = 50_000;
+
+/// Number of edges around the origin sampled to choose the bucket width.
+const DELTA_SAMPLE_EDGES: usize = 1_000;
+
+/// Checks if delta-stepping is expected to be faster than Dijkstra's
+/// algorithm: on large geographs, with several threads available, outside
+/// of the parallel batches, whose threads are already busy.
+pub(crate) fn prefers_delta_stepping(geograph: &Geograph) -> bool {
+    picks_delta_stepping(
+        geograph.len(),
+        rayon::current_num_threads(),
+        rayon::current_thread_index().is_some(),
+    )
+}
+
+fn picks_delta_stepping(nodes: usize, threads: usize, in_batch: bool) -> bool {
+    nodes >= DELTA_STEPPING_MIN_NODES && threads > 1 && !in_batch
+}
+
+/// Determines the cheapest path between two nodes using delta-stepping,
+/// which settles the nodes in buckets of costs `delta` wide, relaxing the
+/// edges of each bucket in parallel. It finds the same cost as Dijkstra's
+/// algorithm, labelling the nodes along the path with their cost.
+///
+/// Light edges, costing up to `delta`, may lead back into the bucket being
+/// settled, which is emptied repeatedly before the heavy edges are relaxed.
+pub(crate) fn delta_stepping(
+    geograph: &Geograph,
+    origin: NodeId,
+    destination: NodeId,
+    start: f64,
+    query: &Query,
+) -> SearchResult {
+    let delta = bucket_width(geograph, origin, query);
+    let bucket = |cost: f64| ((cost - start) / delta) as u64;
+    let mut costs: HashMap<NodeId, f64> = HashMap::from([(origin, start)]);
+    let mut previous: HashMap<NodeId, NodeId> = HashMap::new();
+    let mut buckets: BTreeMap<u64, Vec<NodeId>> = BTreeMap::from([(0, vec![origin])]);
+
+    while let Some((index, mut frontier)) = buckets.pop_first() {
+        let mut settled: Vec<NodeId> = Vec::new();
+        loop {
+            // Skip the nodes moved to a lower bucket since they were queued
+            frontier.retain(|id| bucket(costs[id]) == index);
+            frontier.sort_unstable();
+            frontier.dedup();
+            if frontier.is_empty() {
+                break;
+            }
+            let requests = relax_requests(geograph, &frontier, &costs, query, |cost| cost <= delta);
+            settled.append(&mut frontier);
+            for (neighbor, cost, from) in requests {
+                if costs.get(&neighbor).is_none_or(|&best| cost < best) {
+                    visit_relax(geograph, query, from, neighbor, cost);
+                    costs.insert(neighbor, cost);
+                    previous.insert(neighbor, from);
+                    match bucket(cost) {
+                        next if next == index => frontier.push(neighbor),
+                        next => buckets.entry(next).or_default().push(neighbor),
+                    }
+                }
+            }
+        }
+
+        // Heavy edges always lead to a later bucket
+        settled.sort_unstable();
+        settled.dedup();
+        for id in &settled {
+            if let Some(node) = geograph.get(*id) {
+                query.settle(node, costs[id])?;
+            }
+        }
+        let requests = relax_requests(geograph, &settled, &costs, query, |cost| cost > delta);
+        for (neighbor, cost, from) in requests {
+            if costs.get(&neighbor).is_none_or(|&best| cost < best) {
+                visit_relax(geograph, query, from, neighbor, cost);
+                costs.insert(neighbor, cost);
+                previous.insert(neighbor, from);
+                buckets.entry(bucket(cost)).or_default().push(neighbor);
+            }
+        }
+
+        // Early exit once the bucket of the destination is settled
+        if settled.binary_search(&destination).is_ok() {
+            let mut path = Vec::new();
+            let mut step = destination;
+            while step != origin {
+                path.push((step, costs[&step]));
+                let Some(&prev) = previous.get(&step) else {
+                    return Ok(None);
+                };
+                step = prev;
+            }
+            path.push((origin, start));
+            path.reverse();
+            if let Some(visitor) = query.visitor() {
+                visitor.found(&path);
+            }
+            return Ok(Some(path));
+        }
+    }
+
+    Ok(None) // If no path is found
+}
+
+/// Notifies the visitor of the query, if any, of an edge relaxed by the search.
+fn visit_relax(geograph: &Geograph, query: &Query, from: NodeId, to: NodeId, cost: f64) {
+    // Searches without a visitor skip looking up the nodes
+    let Some(visitor) = query.visitor() else {
+        return;
+    };
+    if let (Some(from), Some(to)) = (geograph.get(from), geograph.get(to)) {
+        visitor.relax(from, to, cost);
+    }
+}
+
+/// Evaluates the edges of the nodes in parallel, returning the neighbors
+/// reached through the edges whose cost matches the filter, with their
+/// total cost and the node they are reached from.
+fn relax_requests(
+    geograph: &Geograph,
+    nodes: &[NodeId],
+    costs: &HashMap<NodeId, f64>,
+    query: &Query,
+    filter: impl Fn(f64) -> bool + Sync,
+) -> Vec<(NodeId, f64, NodeId)> {
+    nodes
+        .par_iter()
+        .filter_map(|&id| Some((geograph.get(id)?, costs[&id])))
+        .flat_map_iter(|(node, cost)| {
+            let filter = &filter;
+            node.waypoints.iter().filter_map(move |&neighbor_id| {
+                let neighbor = geograph.get(neighbor_id).expect("Missing neighbor");
+                let edge = geograph.edge_from(node, neighbor_id);
+                let additional_cost = query.edge_cost(node, neighbor, edge, cost)?;
+                filter(additional_cost).then_some((neighbor_id, cost + additional_cost, node.id))
+            })
+        })
+        .collect()
+}
+
+/// Width of the buckets: the average cost of the edges around the origin,
+/// so that a bucket holds about one edge along a path.
+fn bucket_width(geograph: &Geograph, origin: NodeId, query: &Query) -> f64 {
+    let mut queue = VecDeque::from([origin]);
+    let mut seen = HashSet::from([origin]);
+    let (mut total, mut count) = (0.0, 0);
+    while let Some(node) = queue.pop_front().and_then(|id| geograph.get(id)) {
+        for &neighbor_id in node.waypoints.iter() {
+            let Some(neighbor) = geograph.get(neighbor_id) else {
+                continue;
+            };
+            let edge = geograph.edge_from(node, neighbor_id);
+            if let Some(cost) = query.edge_cost(node, neighbor, edge, 0.0) {
+                total += cost;
+                count += 1;
+            }
+            if seen.insert(neighbor_id) {
+                queue.push_back(neighbor_id);
+            }
+        }
+        if count >= DELTA_SAMPLE_EDGES {
+            break;
+        }
+    }
+    Some(total / count as f64)
+        .filter(|delta| delta.is_finite() && *delta > 0.0)
+        .unwrap_or(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{picks_delta_stepping, DELTA_STEPPING_MIN_NODES};
+    use crate::geograph::route::Query;
+    use crate::geograph::{
+        EdgeAttributes, EdgeCost, Geograph, Haversine, Node, Overlay, PathType, RouteOptions,
+        Search, Weighted,
+    };
+
+    /// A grid of jittered nodes, connected to their neighbors in the four
+    /// directions, with pseudo-random weights.
+    fn grid_fixture(size: i32) -> Geograph {
+        let mut seed: u64 = 42;
+        let mut random = move || {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        };
+        let mut geograph = Geograph::new("Test Grid");
+        for row in 0..size {
+            for col in 0..size {
+                let neighbors = [
+                    (row - 1, col),
+                    (row + 1, col),
+                    (row, col - 1),
+                    (row, col + 1),
+                ];
+                let waypoints: Vec<i32> = neighbors
+                    .into_iter()
+                    .filter(|&(r, c)| (0..size).contains(&r) && (0..size).contains(&c))
+                    .map(|(r, c)| r * size + c)
+                    .collect();
+                let id = row * size + col;
+                let geoloc = (row as f64 + random() * 0.5, col as f64 + random() * 0.5);
+                for &to in &waypoints {
+                    let attributes = EdgeAttributes {
+                        weight: Some(1.0 + random() * 100.0),
+                        ..Default::default()
+                    };
+                    geograph.add_edge(id, to, attributes);
+                }
+                geograph.add(Node::new(id, geoloc, waypoints));
+            }
+        }
+        geograph
+    }
+
+    fn with_search(options: &RouteOptions<'static>, search: Search) -> RouteOptions<'static> {
+        options.clone().with_search(search)
+    }
+
+    #[test]
+    fn it_finds_the_same_costs_as_dijkstra() {
+        let geograph = grid_fixture(20);
+        let pairs = [
+            ((0.0, 0.0), (19.0, 19.0)),
+            ((3.0, 15.0), (17.0, 2.0)),
+            ((10.0, 10.0), (10.5, 10.5)),
+        ];
+        let costs: [&'static (dyn EdgeCost + Sync); 2] = [&Weighted, &Haversine];
+
+        for cost in costs {
+            let options = RouteOptions::new().with_cost(cost);
+            for (from, to) in pairs {
+                let dijkstra = geograph.route(&from, &to, &with_search(&options, Search::Dijkstra));
+                let parallel =
+                    geograph.route(&from, &to, &with_search(&options, Search::DeltaStepping));
+
+                assert!((dijkstra.cost - parallel.cost).abs() < 1e-9);
+                assert_eq!(parallel.nodes.first(), dijkstra.nodes.first());
+                assert_eq!(parallel.nodes.last(), dijkstra.nodes.last());
+            }
+        }
+    }
+
+    #[test]
+    fn it_applies_route_options() {
+        let geograph = grid_fixture(5);
+        let (from, to) = ((0.0, 0.0), (0.0, 4.0));
+        // A wall across the grid, with a gap on the last row
+        let mut overlay = Overlay::new();
+        for row in 0..4 {
+            overlay.close_node(row * 5 + 2);
+        }
+        let options = RouteOptions::new().with_overlay(&overlay);
+
+        let parallel = geograph.route(
+            &from,
+            &to,
+            &options.clone().with_search(Search::DeltaStepping),
+        );
+        let dijkstra = geograph.route(&from, &to, &options.with_search(Search::Dijkstra));
+        assert!(parallel.nodes.contains(&22));
+        assert!((parallel.cost - dijkstra.cost).abs() < 1e-9);
+
+        overlay.close_node(22);
+        let options = RouteOptions::new()
+            .with_overlay(&overlay)
+            .with_search(Search::DeltaStepping);
+        let route = geograph.route(&from, &to, &options);
+        assert_eq!(route.path_type, PathType::Direct);
+    }
+
+    #[test]
+    fn it_picks_delta_stepping_on_large_geographs() {
+        let threshold = DELTA_STEPPING_MIN_NODES;
+        assert!(!picks_delta_stepping(threshold - 1, 8, false));
+        assert!(picks_delta_stepping(threshold, 8, false));
+        // Without idle threads
+        assert!(!picks_delta_stepping(threshold, 1, false));
+        assert!(!picks_delta_stepping(threshold, 8, true));
+
+        let geograph = grid_fixture(5);
+        let options = RouteOptions::new();
+        assert_eq!(options.search, Search::Auto);
+        assert!(!Query::new(&geograph, &options).delta_stepping());
+        let options = options.with_search(Search::DeltaStepping);
+        assert!(Query::new(&geograph, &options).delta_stepping());
+    }
+}
@@ -1,4 +1,16 @@
-use crate::{geograph::ShortestPath, prelude::*};
+mod batch;
+mod delta_stepping;
+mod snap;
+
+use batch::plan_distances;
+pub use delta_stepping::DELTA_STEPPING_MIN_NODES;
+pub(crate) use delta_stepping::{delta_stepping, prefers_delta_stepping};
+pub use snap::{ParallelSnapping, Snap};
+
+use crate::{
+    geograph::{DistanceOnly, Route, RouteOptions, SearchLimits, ShortestPath},
+    prelude::*,
+};
 pub use rayon::prelude::*;
 
 pub struct Leg<T: Geolocalizable + Send>(pub (T, T));
@@ -13,13 +25,54 @@ impl<T: Geolocalizable + Send + Sync> Leg<T> {
 }
 
 pub trait ParallelDistanceCalculator<T: Geolocalizable + Send + Sync> {
+    /// Calculates the shortest paths of the legs in parallel, searching once
+    /// from each node the origins are snapped to, so that repeated legs and
+    /// legs sharing an origin reuse the same search.
     fn par_distance(&self, legs: &[Leg<T>]) -> Vec<ShortestPath>;
+
+    /// Calculates the shortest paths of the legs like `par_distance`, also
+    /// serving the legs going both ways between two places with a single
+    /// search on undirected geographs, such as origin-destination matrices.
+    /// Paths of equal length may be picked differently than `par_distance`.
+    fn par_distance_symmetric(&self, legs: &[Leg<T>]) -> Vec<ShortestPath>;
+
+    /// Calculates the distances of the legs in parallel, without building their paths.
+    fn par_distance_only(&self, legs: &[Leg<T>]) -> Vec<DistanceOnly>;
+
+    /// Calculates the distances of the legs in parallel, aborting the search
+    /// of each leg once it reaches any of the limits. Limits with a deadline
+    /// or a cancellation token abort the legs of the batch still running,
+    /// and skip those not started yet.
+    fn par_distance_within(&self, legs: &[Leg<T>], limits: &SearchLimits) -> Vec<ShortestPath>;
+
+    /// Calculates the routes of the legs in parallel, with the same options for all of them.
+    fn par_route(&self, legs: &[Leg<T>], options: &RouteOptions) -> Vec<Route>;
 }
 
 impl<T: Geolocalizable + Send + Sync> ParallelDistanceCalculator<T> for Geograph {
     fn par_distance(&self, legs: &[Leg<T>]) -> Vec<ShortestPath> {
+        plan_distances(self, legs, false)
+    }
+
+    fn par_distance_symmetric(&self, legs: &[Leg<T>]) -> Vec<ShortestPath> {
+        plan_distances(self, legs, true)
+    }
+
+    fn par_distance_only(&self, legs: &[Leg<T>]) -> Vec<DistanceOnly> {
+        legs.par_iter()
+            .map(|leg| self.distance_only(leg.origin(), leg.destination()))
+            .collect()
+    }
+
+    fn par_distance_within(&self, legs: &[Leg<T>], limits: &SearchLimits) -> Vec<ShortestPath> {
+        legs.par_iter()
+            .map(|leg| self.shortest_path_within(leg.origin(), leg.destination(), limits))
+            .collect()
+    }
+
+    fn par_route(&self, legs: &[Leg<T>], options: &RouteOptions) -> Vec<Route> {
         legs.par_iter()
-            .map(|leg| self.shortest_path(leg.origin(), leg.destination()))
+            .map(|leg| self.route(leg.origin(), leg.destination(), options))
             .collect()
     }
 }
@@ -54,8 +107,62 @@ mod tests {
         let (distance, path, path_type) = first;
 
         assert_eq!(shortest_paths.len(), legs.len());
-        assert_eq!(*distance, 14116.87577572815);
+        assert_eq!(distance.km(), 14116.87577572815);
         assert_eq!(path.len(), 6);
         assert_eq!(*path_type, PathType::ViaWaypoints);
     }
+
+    #[test]
+    fn it_calculates_distance_only() {
+        let geograph = geograph_fixture();
+        let legs = [
+            Leg(((0.0, 0.0), (5.0, 5.0))),
+            Leg(((4.5, 4.0), (0.5, 0.0))),
+            Leg(((2.0, 2.0), (2.0, 2.0))),
+        ];
+
+        let distances = geograph.par_distance_only(&legs);
+        for ((distance, path_type), (expected, _, expected_type)) in
+            distances.iter().zip(geograph.par_distance(&legs))
+        {
+            assert!((distance.km() - expected.km()).abs() < 1e-9);
+            assert_eq!(*path_type, expected_type);
+        }
+    }
+
+    #[test]
+    fn it_aborts_cancelled_batches() {
+        let geograph = geograph_fixture();
+        let legs: Vec<_> = (0..10).map(|_| Leg(((0.0, 0.0), (5.0, 5.0)))).collect();
+        let token = CancellationToken::new();
+        let limits = SearchLimits::new().with_cancellation(&token);
+
+        let shortest_paths = geograph.par_distance_within(&legs, &limits);
+        assert!(shortest_paths
+            .iter()
+            .all(|(_, _, path_type)| *path_type == PathType::ViaWaypoints));
+
+        token.cancel();
+        let shortest_paths = geograph.par_distance_within(&legs, &limits);
+        assert!(shortest_paths
+            .iter()
+            .all(|(_, _, path_type)| *path_type == PathType::Aborted(Limit::Cancelled)));
+
+        let limits = SearchLimits::new().with_max_settled(1);
+        let (_, _, path_type) = geograph.shortest_path_within(&(0.0, 0.0), &(5.0, 5.0), &limits);
+        assert_eq!(path_type, PathType::Aborted(Limit::MaxSettled));
+    }
+
+    #[test]
+    fn it_calculates_routes_with_options() {
+        let geograph = geograph_fixture();
+        let legs: Vec<_> = (0..10).map(|_| Leg(((0.0, 0.0), (5.0, 5.0)))).collect();
+        let mut overlay = Overlay::new();
+        overlay.close_node(2);
+
+        let routes = geograph.par_route(&legs, &RouteOptions::new().with_overlay(&overlay));
+
+        assert_eq!(routes.len(), legs.len());
+        assert!(routes.iter().all(|route| !route.nodes.contains(&2)));
+    }
 }
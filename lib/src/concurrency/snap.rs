@@ -0,0 +1,189 @@
+use rayon::prelude::*;
+
+use crate::geograph::{Distance, Geograph, Geoloc, Geolocalizable, NodeId};
+
+/// Represents a point snapped to the closest node of a geograph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snap {
+    pub node: NodeId,
+    /// Haversine distance between the point and the node.
+    pub distance: Distance,
+    /// Whether the node is further than the radius of the snapping.
+    pub beyond_radius: bool,
+}
+
+pub trait ParallelSnapping<T: Geolocalizable + Send + Sync> {
+    /// Snaps the points to their closest nodes in parallel, or `None`
+    /// on empty geographs.
+    fn par_snap(&self, points: &[T]) -> Vec<Option<Snap>>;
+
+    /// Snaps the points to their closest nodes in parallel like `par_snap`,
+    /// flagging the points further than the radius from their closest node.
+    fn par_snap_within(&self, points: &[T], radius: Distance) -> Vec<Option<Snap>>;
+}
+
+impl<T: Geolocalizable + Send + Sync> ParallelSnapping<T> for Geograph {
+    fn par_snap(&self, points: &[T]) -> Vec<Option<Snap>> {
+        snap(self, points, None)
+    }
+
+    fn par_snap_within(&self, points: &[T], radius: Distance) -> Vec<Option<Snap>> {
+        snap(self, points, Some(radius))
+    }
+}
+
+fn snap<T: Geolocalizable + Send + Sync>(
+    geograph: &Geograph,
+    points: &[T],
+    radius: Option<Distance>,
+) -> Vec<Option<Snap>> {
+    let index = NodeIndex::new(geograph);
+    points
+        .par_iter()
+        .map(|point| {
+            let node = geograph.get(index.closest(point.geoloc())?)?;
+            let distance = Distance::from_km(point.haversine(node));
+            Some(Snap {
+                node: node.id,
+                distance,
+                beyond_radius: radius.is_some_and(|radius| distance.km() > radius.km()),
+            })
+        })
+        .collect()
+}
+
+/// Cartesian coordinates of a geolocation on the unit sphere, whose straight
+/// line distances grow with the great circle distances.
+type UnitVector = [f64; 3];
+
+fn unit_vector((lat, lng): Geoloc) -> UnitVector {
+    let (lat, lng) = (lat.to_radians(), lng.to_radians());
+    [lat.cos() * lng.cos(), lat.cos() * lng.sin(), lat.sin()]
+}
+
+fn squared_distance(a: &UnitVector, b: &UnitVector) -> f64 {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
+
+/// k-d tree of the nodes of a geograph, on their unit vectors, to find the
+/// closest node to many points without going through all the nodes.
+struct NodeIndex {
+    /// Nodes ordered so that the median of each slice splits it on the axis
+    /// of its depth.
+    nodes: Vec<(UnitVector, NodeId)>,
+}
+
+impl NodeIndex {
+    fn new(geograph: &Geograph) -> Self {
+        let mut nodes: Vec<(UnitVector, NodeId)> = geograph
+            .iter_nodes()
+            .map(|node| (unit_vector(node.geoloc()), node.id))
+            .collect();
+        Self::split(&mut nodes, 0);
+        Self { nodes }
+    }
+
+    fn split(nodes: &mut [(UnitVector, NodeId)], depth: usize) {
+        if nodes.len() <= 1 {
+            return;
+        }
+        let (axis, median) = (depth % 3, nodes.len() / 2);
+        nodes.select_nth_unstable_by(median, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+        let (lower, upper) = nodes.split_at_mut(median);
+        Self::split(lower, depth + 1);
+        Self::split(&mut upper[1..], depth + 1);
+    }
+
+    fn closest(&self, geoloc: Geoloc) -> Option<NodeId> {
+        let mut best = None;
+        Self::search(&self.nodes, 0, &unit_vector(geoloc), &mut best);
+        best.map(|(_, id)| id)
+    }
+
+    fn search(
+        nodes: &[(UnitVector, NodeId)],
+        depth: usize,
+        target: &UnitVector,
+        best: &mut Option<(f64, NodeId)>,
+    ) {
+        if nodes.is_empty() {
+            return;
+        }
+        let (axis, median) = (depth % 3, nodes.len() / 2);
+        let (vector, id) = &nodes[median];
+        let distance = squared_distance(vector, target);
+        if best.is_none_or(|(closest, _)| distance < closest) {
+            *best = Some((distance, *id));
+        }
+
+        // Search the side of the target first, and the other one only if
+        // it may hold a closer node
+        let offset = target[axis] - vector[axis];
+        let (near, far) = if offset < 0.0 {
+            (&nodes[..median], &nodes[median + 1..])
+        } else {
+            (&nodes[median + 1..], &nodes[..median])
+        };
+        Self::search(near, depth + 1, target, best);
+        if best.is_none_or(|(closest, _)| offset * offset < closest) {
+            Self::search(far, depth + 1, target, best);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geograph::Node;
+
+    /// Nodes spread over the globe, including around the poles and the antimeridian.
+    fn geograph_fixture() -> Geograph {
+        let mut geograph = Geograph::new("Test Geograph");
+        let mut id = 0;
+        for lat in (-85..=85).step_by(17) {
+            for lng in (-180..180).step_by(23) {
+                geograph.add(Node::new(id, (lat as f64, lng as f64 + 0.5), vec![]));
+                id += 1;
+            }
+        }
+        geograph
+    }
+
+    #[test]
+    fn it_snaps_to_the_closest_nodes() {
+        let geograph = geograph_fixture();
+        let points: Vec<Geoloc> = (0..500)
+            .map(|i| {
+                let i = i as f64;
+                ((i * 37.3) % 180.0 - 90.0, (i * 71.9) % 360.0 - 180.0)
+            })
+            .collect();
+
+        let snaps = geograph.par_snap(&points);
+        for (point, snap) in points.iter().zip(snaps) {
+            let snap = snap.unwrap();
+            let closest = geograph.closest_where(point, |_| true).unwrap();
+            assert!((snap.distance.km() - point.haversine(closest)).abs() < 1e-6);
+            assert!(!snap.beyond_radius);
+        }
+    }
+
+    #[test]
+    fn it_flags_points_beyond_the_radius() {
+        let geograph = geograph_fixture();
+        let points = [(0.0, 4.5), (0.0, 179.9)];
+
+        let snaps = geograph.par_snap_within(&points, Distance::from_km(50.0));
+        let snap = snaps[0].unwrap();
+        assert_eq!(geograph.get(snap.node).unwrap().geoloc(), (0.0, 4.5));
+        assert_eq!(snap.distance.km(), 0.0);
+        assert!(!snap.beyond_radius);
+        // The closest node is across the antimeridian
+        let snap = snaps[1].unwrap();
+        assert_eq!(geograph.get(snap.node).unwrap().geoloc(), (0.0, -179.5));
+        assert!(snap.beyond_radius);
+
+        let empty = Geograph::new("Empty Geograph");
+        assert_eq!(empty.par_snap(&points), vec![None, None]);
+    }
+}
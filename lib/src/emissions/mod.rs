@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::geograph::{Distance, Route};
+
+const GRAMS_PER_KG: f64 = 1_000.0;
+
+/// Types of vessels of maritime transport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum VesselType {
+    ContainerShip,
+    BulkCarrier,
+    Tanker,
+    GeneralCargo,
+    RoRo,
+}
+
+/// Traction of rail transport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Traction {
+    Diesel,
+    Electric,
+}
+
+/// Classes of trucks of road transport, by gross vehicle weight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum TruckClass {
+    /// Rigid truck up to 12 tonnes.
+    LightRigid,
+    /// Rigid truck from 12 to 26 tonnes.
+    HeavyRigid,
+    /// Articulated truck up to 40 tonnes.
+    Articulated,
+}
+
+/// Transport modes with an emission factor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum TransportMode {
+    Maritime(VesselType),
+    Rail(Traction),
+    Road(TruckClass),
+}
+
+/// Illustrative well-to-wheel emission intensities, in grams of CO2e per
+/// tonne-km. They are of the order of published defaults, such as those of
+/// the GLEC Framework, but are not taken from a given edition or table, so
+/// they are only fit for examples and tests, not for reporting.
+const ILLUSTRATIVE_FACTORS: [(TransportMode, f64); 10] = [
+    (TransportMode::Maritime(VesselType::ContainerShip), 13.5),
+    (TransportMode::Maritime(VesselType::BulkCarrier), 4.5),
+    (TransportMode::Maritime(VesselType::Tanker), 5.5),
+    (TransportMode::Maritime(VesselType::GeneralCargo), 14.0),
+    (TransportMode::Maritime(VesselType::RoRo), 45.0),
+    (TransportMode::Rail(Traction::Diesel), 26.0),
+    (TransportMode::Rail(Traction::Electric), 12.0),
+    (TransportMode::Road(TruckClass::LightRigid), 195.0),
+    (TransportMode::Road(TruckClass::HeavyRigid), 135.0),
+    (TransportMode::Road(TruckClass::Articulated), 78.0),
+];
+
+/// Emission intensity factors per transport mode, used to calculate the
+/// well-to-wheel CO2e emissions of routes.
+///
+/// Emissions are the product of the tonne-kilometres transported and the
+/// factor of the transport mode, in grams of CO2e per tonne-km, as in the
+/// GLEC Framework. No sourced factors are bundled: callers set the factors
+/// of the modes they use, from carrier data or the edition of the GLEC
+/// Framework they report with.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EmissionFactors {
+    factors: HashMap<TransportMode, f64>,
+}
+
+impl EmissionFactors {
+    /// Factors without any transport mode, to be set with `with`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Illustrative factors for every transport mode, for examples and tests.
+    /// They are not sourced, so they must not be used for reporting.
+    pub fn illustrative() -> Self {
+        Self {
+            factors: ILLUSTRATIVE_FACTORS.into_iter().collect(),
+        }
+    }
+
+    /// Sets the factor of a transport mode, in grams of CO2e per tonne-km.
+    pub fn with(mut self, mode: TransportMode, grams_per_tonne_km: f64) -> Self {
+        self.factors.insert(mode, grams_per_tonne_km);
+        self
+    }
+
+    pub fn factor(&self, mode: TransportMode) -> Option<f64> {
+        self.factors.get(&mode).copied()
+    }
+
+    /// Emissions of transporting the cargo, in tonnes, over a distance.
+    ///
+    /// Panics if the factor of the transport mode was not set, as are the
+    /// emissions of routes and legs.
+    pub fn leg(&self, mode: TransportMode, distance: Distance, cargo_tonnes: f64) -> LegEmissions {
+        let factor = self
+            .factor(mode)
+            .unwrap_or_else(|| panic!("No emission factor for {mode:?}"));
+        let tonne_km = distance.km() * cargo_tonnes;
+        LegEmissions {
+            mode,
+            distance,
+            tonne_km,
+            co2e_kg: tonne_km * factor / GRAMS_PER_KG,
+        }
+    }
+
+    /// Emissions of transporting the cargo, in tonnes, along a route.
+    pub fn route(&self, route: &Route, mode: TransportMode, cargo_tonnes: f64) -> LegEmissions {
+        self.leg(mode, route.distance, cargo_tonnes)
+    }
+
+    /// Emissions of transporting the cargo, in tonnes, along consecutive
+    /// routes using different transport modes, with a breakdown per leg.
+    pub fn legs<'r>(
+        &self,
+        legs: impl IntoIterator<Item = (&'r Route, TransportMode)>,
+        cargo_tonnes: f64,
+    ) -> Emissions {
+        let legs: Vec<LegEmissions> = legs
+            .into_iter()
+            .map(|(route, mode)| self.route(route, mode, cargo_tonnes))
+            .collect();
+        Emissions {
+            co2e_kg: legs.iter().map(|leg| leg.co2e_kg).sum(),
+            legs,
+        }
+    }
+}
+
+/// Emissions of a leg travelled with a single transport mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LegEmissions {
+    pub mode: TransportMode,
+    pub distance: Distance,
+    pub tonne_km: f64,
+    /// Well-to-wheel emissions, in kilograms of CO2e.
+    pub co2e_kg: f64,
+}
+
+/// Emissions of a multi-leg route, with a breakdown per leg.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Emissions {
+    /// Well-to-wheel emissions of all legs, in kilograms of CO2e.
+    pub co2e_kg: f64,
+    pub legs: Vec<LegEmissions>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(km: f64) -> Route {
+        let mut route = Route::direct((0.0, 0.0), (0.0, 1.0));
+        route.distance = Distance::from_km(km);
+        route
+    }
+
+    #[test]
+    fn it_calculates_emissions() {
+        let factors = EmissionFactors::illustrative();
+        let container = TransportMode::Maritime(VesselType::ContainerShip);
+        let leg = factors.leg(container, Distance::from_km(1_000.0), 20.0);
+
+        assert_eq!(leg.tonne_km, 20_000.0);
+        assert_eq!(leg.co2e_kg, 270.0);
+        assert!(ILLUSTRATIVE_FACTORS
+            .iter()
+            .all(|&(mode, _)| factors.factor(mode) > Some(0.0)));
+        assert_eq!(EmissionFactors::new().factor(container), None);
+    }
+
+    #[test]
+    fn it_overrides_factors() {
+        let electric = TransportMode::Rail(Traction::Electric);
+        let factors = EmissionFactors::new().with(electric, 5.0);
+        let leg = factors.leg(electric, Distance::from_km(100.0), 10.0);
+
+        assert_eq!(leg.co2e_kg, 5.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "No emission factor")]
+    fn it_requires_the_factor_of_the_mode() {
+        let electric = TransportMode::Rail(Traction::Electric);
+        let factors = EmissionFactors::new().with(electric, 5.0);
+        factors.leg(
+            TransportMode::Rail(Traction::Diesel),
+            Distance::from_km(1.0),
+            1.0,
+        );
+    }
+
+    #[test]
+    fn it_breaks_down_multi_leg_emissions() {
+        let factors = EmissionFactors::illustrative();
+        let (pre_carriage, main, on_carriage) = (route(50.0), route(10_000.0), route(200.0));
+        let truck = TransportMode::Road(TruckClass::Articulated);
+        let emissions = factors.legs(
+            [
+                (&pre_carriage, truck),
+                (&main, TransportMode::Maritime(VesselType::ContainerShip)),
+                (&on_carriage, TransportMode::Rail(Traction::Diesel)),
+            ],
+            10.0,
+        );
+
+        assert_eq!(emissions.legs.len(), 3);
+        assert_eq!(emissions.legs[0].co2e_kg, 39.0);
+        assert_eq!(emissions.legs[1].co2e_kg, 1_350.0);
+        assert_eq!(emissions.legs[2].co2e_kg, 52.0);
+        assert_eq!(emissions.co2e_kg, 1_441.0);
+    }
+}
@@ -0,0 +1,355 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Geoloc, Geolocalizable, LngLat};
+
+/// Planar point, as (lng, lat) degrees.
+type Point = (f64, f64);
+
+/// Bounding box of a set of points, used to quickly discard
+/// locations far away from a polygon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BBox {
+    min: Point,
+    max: Point,
+}
+
+impl BBox {
+    fn of(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        points.into_iter().fold(None, |bbox, (x, y)| {
+            Some(match bbox {
+                None => BBox {
+                    min: (x, y),
+                    max: (x, y),
+                },
+                Some(BBox { min, max }) => BBox {
+                    min: (min.0.min(x), min.1.min(y)),
+                    max: (max.0.max(x), max.1.max(y)),
+                },
+            })
+        })
+    }
+
+    fn union(self, other: BBox) -> BBox {
+        BBox {
+            min: (self.min.0.min(other.min.0), self.min.1.min(other.min.1)),
+            max: (self.max.0.max(other.max.0), self.max.1.max(other.max.1)),
+        }
+    }
+
+    fn contains(&self, (x, y): Point) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y)
+    }
+
+    fn overlaps(&self, other: &BBox) -> bool {
+        self.min.0 <= other.max.0
+            && other.min.0 <= self.max.0
+            && self.min.1 <= other.max.1
+            && other.min.1 <= self.max.1
+    }
+}
+
+/// A polygon made of GeoJSON-style linear rings: the first ring is the
+/// exterior boundary, and any following rings are holes. Rings may be
+/// closed (repeating their first point at the end) or not.
+///
+/// Containment is computed on a plane of longitude and latitude degrees, so
+/// polygons crossing the antimeridian must be split in two.
+///
+/// When serialized, it is represented as the coordinates of a GeoJSON
+/// polygon: a list of rings of `[lng, lat]` positions.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(from = "Vec<Vec<LngLat>>", into = "Vec<Vec<LngLat>>")
+)]
+pub struct Polygon {
+    rings: Vec<Vec<LngLat>>,
+    bbox: Option<BBox>,
+}
+
+impl Polygon {
+    pub fn new(rings: Vec<Vec<LngLat>>) -> Self {
+        let bbox = rings
+            .first()
+            .and_then(|exterior| BBox::of(exterior.iter().map(point)));
+        Self { rings, bbox }
+    }
+
+    pub fn rings(&self) -> &[Vec<LngLat>] {
+        &self.rings
+    }
+
+    /// Checks if the location is inside the polygon, outside of its holes.
+    pub fn contains(&self, loc: &impl Geolocalizable) -> bool {
+        let p = point(loc);
+        match self.bbox {
+            Some(bbox) if bbox.contains(p) => {
+                let mut rings = self.rings.iter();
+                rings
+                    .next()
+                    .is_some_and(|exterior| ring_contains(exterior, p))
+                    && !rings.any(|hole| ring_contains(hole, p))
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks if the straight segment between two locations crosses
+    /// any of the polygon boundaries.
+    pub fn crosses(&self, from: &impl Geolocalizable, to: &impl Geolocalizable) -> bool {
+        self.crosses_points(point(from), point(to))
+    }
+
+    fn crosses_points(&self, a: Point, b: Point) -> bool {
+        // Segments crossing the antimeridian are unwrapped from both of their ends
+        if (b.0 - a.0).abs() > 180.0 {
+            let shift = 360.0_f64.copysign(a.0 - b.0);
+            return self.crosses_segment(a, (b.0 + shift, b.1))
+                || self.crosses_segment((a.0 - shift, a.1), b);
+        }
+        self.crosses_segment(a, b)
+    }
+
+    fn crosses_segment(&self, a: Point, b: Point) -> bool {
+        let segment = BBox::of([a, b]).expect("Segment has two points");
+        match self.bbox {
+            Some(bbox) if bbox.overlaps(&segment) => self
+                .rings
+                .iter()
+                .any(|ring| ring_segments(ring).any(|(c, d)| segments_intersect(a, b, c, d))),
+            _ => false,
+        }
+    }
+}
+
+impl From<Vec<Vec<LngLat>>> for Polygon {
+    fn from(rings: Vec<Vec<LngLat>>) -> Self {
+        Self::new(rings)
+    }
+}
+
+impl From<Polygon> for Vec<Vec<LngLat>> {
+    fn from(polygon: Polygon) -> Self {
+        polygon.rings
+    }
+}
+
+fn point(loc: &impl Geolocalizable) -> Point {
+    (loc.lng(), loc.lat())
+}
+
+fn ring_segments(ring: &[LngLat]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    let points = ring.iter().map(point);
+    points.clone().zip(points.cycle().skip(1)).take(ring.len())
+}
+
+/// Ray casting point-in-ring test.
+fn ring_contains(ring: &[LngLat], (x, y): Point) -> bool {
+    ring_segments(ring)
+        .filter(|&((x1, y1), (x2, y2))| {
+            (y1 > y) != (y2 > y) && x < (x2 - x1) * (y - y1) / (y2 - y1) + x1
+        })
+        .count()
+        % 2
+        == 1
+}
+
+fn orientation(a: Point, b: Point, c: Point) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+fn segments_intersect(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let within = |p: Point, q: Point, r: Point| {
+        r.0 >= p.0.min(q.0) && r.0 <= p.0.max(q.0) && r.1 >= p.1.min(q.1) && r.1 <= p.1.max(q.1)
+    };
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+
+    if o1 * o2 < 0.0 && o3 * o4 < 0.0 {
+        return true;
+    }
+    // Collinear and touching cases
+    (o1 == 0.0 && within(a, b, c))
+        || (o2 == 0.0 && within(a, b, d))
+        || (o3 == 0.0 && within(c, d, a))
+        || (o4 == 0.0 && within(c, d, b))
+}
+
+/// Exclusion zones of a route calculation, precomputed once per query
+/// so that nodes and edges far away from every polygon are discarded
+/// with a single bounding box check.
+pub(crate) struct Exclusions<'a> {
+    polygons: &'a [Polygon],
+    bbox: Option<BBox>,
+}
+
+impl<'a> Exclusions<'a> {
+    pub fn new(polygons: &'a [Polygon]) -> Self {
+        let bbox = polygons
+            .iter()
+            .filter_map(|polygon| polygon.bbox)
+            .reduce(BBox::union);
+        Self { polygons, bbox }
+    }
+
+    pub fn excludes_node(&self, loc: &impl Geolocalizable) -> bool {
+        match self.bbox {
+            Some(bbox) if bbox.contains(point(loc)) => {
+                self.polygons.iter().any(|polygon| polygon.contains(loc))
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks if the edge, following its shape if any, crosses an exclusion zone.
+    pub fn excludes_edge(
+        &self,
+        from: &impl Geolocalizable,
+        to: &impl Geolocalizable,
+        geometry: Option<&[Geoloc]>,
+    ) -> bool {
+        let Some(bbox) = self.bbox else {
+            return false;
+        };
+        let geometry = geometry.unwrap_or_default();
+        let points = || {
+            std::iter::once(point(from))
+                .chain(geometry.iter().map(point))
+                .chain(std::iter::once(point(to)))
+        };
+        let segments = || points().zip(points().skip(1));
+
+        // Edges away from every zone are kept with a single bounding box check,
+        // unless they cross the antimeridian, where their box would be inverted
+        let wraps = segments().any(|(a, b)| (b.0 - a.0).abs() > 180.0);
+        let edge = BBox::of(points()).expect("Edge has two points");
+        if !wraps && !bbox.overlaps(&edge) {
+            return false;
+        }
+        segments().any(|(a, b)| {
+            self.polygons
+                .iter()
+                .any(|polygon| polygon.crosses_points(a, b))
+        }) || geometry.iter().any(|point| self.excludes_node(point))
+    }
+}
+
+/// Ellipse around the entry and exit nodes of a route calculation: the
+/// locations whose haversine detour between them stays within a factor of
+/// the direct distance. Searches restricted to it skip the nodes far out of
+/// the way, without any preprocessing.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Corridor {
+    foci: (Geoloc, Geoloc),
+    max_detour: f64,
+}
+
+impl Corridor {
+    pub fn new(from: &impl Geolocalizable, to: &impl Geolocalizable, factor: f64) -> Self {
+        Self {
+            foci: (from.geoloc(), to.geoloc()),
+            max_detour: from.haversine(to) * factor,
+        }
+    }
+
+    pub fn contains(&self, loc: &impl Geolocalizable) -> bool {
+        loc.haversine(&self.foci.0) + loc.haversine(&self.foci.1) <= self.max_detour
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(min: f64, max: f64) -> Vec<LngLat> {
+        [(min, min), (max, min), (max, max), (min, max)]
+            .iter()
+            .map(|&(lng, lat)| LngLat::new(lng, lat).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_corridor() {
+        let corridor = Corridor::new(&(0.0, 0.0), &(0.0, 2.0), 1.2);
+
+        assert!(corridor.contains(&(0.0, 0.0)));
+        assert!(corridor.contains(&(0.0, 2.0)));
+        assert!(corridor.contains(&(0.5, 1.0)));
+        assert!(!corridor.contains(&(1.0, 1.0)));
+        assert!(!corridor.contains(&(0.0, 2.5)));
+    }
+
+    #[test]
+    fn test_contains() {
+        let polygon = Polygon::new(vec![square(0.0, 10.0), square(4.0, 6.0)]);
+
+        assert!(polygon.contains(&(2.0, 2.0)));
+        assert!(polygon.contains(&(8.0, 5.0)));
+        assert!(!polygon.contains(&(5.0, 5.0))); // In the hole
+        assert!(!polygon.contains(&(11.0, 5.0)));
+        assert!(!Polygon::new(vec![]).contains(&(0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_crosses() {
+        let polygon = Polygon::new(vec![square(0.0, 10.0)]);
+
+        assert!(polygon.crosses(&(5.0, -5.0), &(5.0, 15.0)));
+        assert!(polygon.crosses(&(5.0, 5.0), &(5.0, 15.0)));
+        assert!(!polygon.crosses(&(-5.0, -5.0), &(-5.0, 15.0)));
+        assert!(!polygon.crosses(&(2.0, 2.0), &(8.0, 8.0)));
+    }
+
+    #[test]
+    fn test_crosses_antimeridian() {
+        let band = |min_lng: f64, max_lng: f64| {
+            let ring = [
+                (min_lng, 0.0),
+                (max_lng, 0.0),
+                (max_lng, 10.0),
+                (min_lng, 10.0),
+            ]
+            .iter()
+            .map(|&(lng, lat)| LngLat::new(lng, lat).unwrap())
+            .collect();
+            Polygon::new(vec![ring])
+        };
+        let (from, to) = ((5.0, 175.0), (5.0, -175.0));
+
+        assert!(band(170.0, 179.0).crosses(&from, &to));
+        assert!(band(-179.0, -170.0).crosses(&from, &to));
+        assert!(!band(-10.0, 10.0).crosses(&from, &to));
+
+        // Edges across the antimeridian are not discarded by their bounding box
+        let polygons = [band(176.0, 179.0)];
+        let exclusions = Exclusions::new(&polygons);
+        assert!(exclusions.excludes_edge(&from, &to, None));
+        assert!(!exclusions.excludes_edge(&(5.0, -175.0), &(5.0, -170.0), None));
+    }
+
+    #[test]
+    fn test_exclusions() {
+        let polygons = vec![Polygon::new(vec![square(0.0, 10.0)])];
+        let exclusions = Exclusions::new(&polygons);
+
+        assert!(exclusions.excludes_node(&(5.0, 5.0)));
+        assert!(!exclusions.excludes_node(&(20.0, 20.0)));
+        assert!(exclusions.excludes_edge(&(-5.0, 5.0), &(15.0, 5.0), None));
+        assert!(exclusions.excludes_edge(&(-5.0, -5.0), &(-5.0, 20.0), Some(&[(5.0, 5.0)])));
+        assert!(!exclusions.excludes_edge(&(-5.0, -5.0), &(-5.0, 20.0), None));
+        assert!(!Exclusions::new(&[]).excludes_edge(&(-5.0, 5.0), &(15.0, 5.0), None));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_reads_geojson_rings() {
+        let polygon: Polygon =
+            serde_json::from_str("[[[0, 0], [10, 0], [10, 10], [0, 10], [0, 0]]]").unwrap();
+
+        assert!(polygon.contains(&(5.0, 5.0)));
+        assert_eq!(polygon.rings()[0].len(), 5);
+        assert!(serde_json::from_str::<Polygon>("[[[0, 100], [1, 1], [2, 2]]]").is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::{Distance, Geoloc, Geolocalizable, Node, NodeId};
+
+/// Nodes along the path found by a search, labelled with their cost,
+/// `None` if the destination is not reachable, or the limit which
+/// aborted the search.
+pub(crate) type SearchResult = Result<Option<Vec<(NodeId, f64)>>, Limit>;
+
+/// Limits of a search, which is aborted once it reaches any of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// The search settled the maximum number of nodes.
+    MaxSettled,
+    /// The search settled a node beyond the maximum distance from the origin.
+    MaxDistance,
+    /// The deadline passed.
+    Deadline,
+    /// The search was cancelled.
+    Cancelled,
+}
+
+/// Cancels the searches sharing it, such as those of a batch, from any
+/// thread. Clones of a token cancel the same searches.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aborts the searches running with the token, and those starting later.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Bounds the work of a route search, to keep the latency of pathological
+/// queries in check. Searches reaching a limit return a route whose path
+/// type is `PathType::Aborted`, with the limit reached.
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits {
+    max_settled: Option<usize>,
+    max_distance: Option<Distance>,
+    deadline: Option<Instant>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl SearchLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of nodes settled by each search, including
+    /// its retries within a widened corridor. Multi-label searches count the
+    /// labels they extend instead.
+    pub fn with_max_settled(mut self, nodes: usize) -> Self {
+        self.max_settled = Some(nodes);
+        self
+    }
+
+    /// Sets the maximum haversine distance between the nodes settled by the
+    /// search and the node closest to the origin, which bounds the area
+    /// explored whatever the cost minimized.
+    pub fn with_max_distance(mut self, distance: Distance) -> Self {
+        self.max_distance = Some(distance);
+        self
+    }
+
+    /// Sets the time at which the searches are aborted. Shared by a batch
+    /// of searches, it bounds the time taken by the whole batch.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the deadline of the searches to the given time from now.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Sets the token cancelling the searches.
+    pub fn with_cancellation(mut self, token: &CancellationToken) -> Self {
+        self.cancellation = Some(token.clone());
+        self
+    }
+
+    /// Checks if the searches are cancelled or past their deadline,
+    /// the limits which don't depend on the search.
+    pub(crate) fn expired(&self) -> Option<Limit> {
+        if self
+            .cancellation
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+        {
+            return Some(Limit::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Some(Limit::Deadline);
+        }
+        None
+    }
+}
+
+/// Work done by a search, counted against its limits.
+pub(crate) struct Budget<'a> {
+    limits: &'a SearchLimits,
+    origin: Geoloc,
+    settled: AtomicUsize,
+}
+
+impl<'a> Budget<'a> {
+    pub fn new(limits: &'a SearchLimits, origin: &Node) -> Self {
+        Self {
+            limits,
+            origin: origin.geoloc(),
+            settled: AtomicUsize::new(0),
+        }
+    }
+
+    /// Counts a node settled by the search, or returns the limit reached.
+    pub fn settle(&self, node: &Node) -> Result<(), Limit> {
+        let settled = self.settled.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.limits.max_settled.is_some_and(|max| settled > max) {
+            return Err(Limit::MaxSettled);
+        }
+        let max_distance = self.limits.max_distance;
+        if max_distance.is_some_and(|max| node.haversine(&self.origin) > max.km()) {
+            return Err(Limit::MaxDistance);
+        }
+        self.limits.expired().map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geograph::{Geograph, PathType, RouteOptions};
+
+    /// A line along the equator, one degree apart.
+    fn geograph_fixture() -> Geograph {
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (0.0, 0.0), vec![1]))
+            .add(Node::new(1, (0.0, 1.0), vec![0, 2]))
+            .add(Node::new(2, (0.0, 2.0), vec![1, 3]))
+            .add(Node::new(3, (0.0, 3.0), vec![2]));
+        geograph
+    }
+
+    fn route(geograph: &Geograph, limits: SearchLimits) -> PathType {
+        let options = RouteOptions::new().with_limits(limits);
+        geograph.route(&(0.0, 0.0), &(0.0, 3.0), &options).path_type
+    }
+
+    #[test]
+    fn test_max_settled() {
+        let geograph = geograph_fixture();
+
+        let limits = SearchLimits::new().with_max_settled(3);
+        assert_eq!(
+            route(&geograph, limits),
+            PathType::Aborted(Limit::MaxSettled)
+        );
+        let limits = SearchLimits::new().with_max_settled(4);
+        assert_eq!(route(&geograph, limits), PathType::ViaWaypoints);
+    }
+
+    #[test]
+    fn test_max_distance() {
+        let geograph = geograph_fixture();
+
+        let limits = SearchLimits::new().with_max_distance(Distance::from_km(250.0));
+        assert_eq!(
+            route(&geograph, limits),
+            PathType::Aborted(Limit::MaxDistance)
+        );
+        let limits = SearchLimits::new().with_max_distance(Distance::from_km(350.0));
+        assert_eq!(route(&geograph, limits), PathType::ViaWaypoints);
+    }
+
+    #[test]
+    fn test_deadline_and_cancellation() {
+        let geograph = geograph_fixture();
+
+        let limits = SearchLimits::new().with_deadline(Instant::now());
+        assert_eq!(route(&geograph, limits), PathType::Aborted(Limit::Deadline));
+        let limits = SearchLimits::new().with_timeout(Duration::from_secs(60));
+        assert_eq!(route(&geograph, limits), PathType::ViaWaypoints);
+
+        let token = CancellationToken::new();
+        let limits = SearchLimits::new().with_cancellation(&token);
+        assert_eq!(route(&geograph, limits.clone()), PathType::ViaWaypoints);
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(
+            route(&geograph, limits),
+            PathType::Aborted(Limit::Cancelled)
+        );
+    }
+}
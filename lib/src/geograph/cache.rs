@@ -0,0 +1,373 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::route::Query;
+use super::{
+    Geograph, Geoloc, Geolocalizable, Node, NodeId, Path, PathType, Route, RouteOptions,
+    ShortestPath,
+};
+
+/// First line of the files caches are saved to, identifying their format.
+const HEADER: &str = "routrs-route-cache 2";
+
+/// Ids of the nodes closest to the origin and destination of a path.
+type Endpoints = (NodeId, NodeId);
+
+/// Geolocations along the part of a path on the geograph, from the node
+/// closest to its origin to the one closest to its destination, or `None`
+/// if the destination is not reachable.
+type CachedPath = Option<Arc<[Geoloc]>>;
+
+/// Hit and miss statistics of a `RouteCache`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Number of paths in the cache.
+    pub entries: usize,
+}
+
+impl CacheStats {
+    /// Share of the lookups found in the cache, or zero without lookups.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    paths: HashMap<Endpoints, (CachedPath, u64)>,
+    /// Endpoints of the paths by the time they were last used, oldest first.
+    recency: BTreeMap<u64, Endpoints>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl Entries {
+    fn touch(&mut self, endpoints: Endpoints) -> u64 {
+        self.clock += 1;
+        self.recency.insert(self.clock, endpoints);
+        self.clock
+    }
+}
+
+/// Thread-safe cache of the shortest paths between the nodes of a geograph,
+/// used by `Geograph::shortest_path_cached`. It keeps the paths of the most
+/// recently used pairs of nodes closest to the origin and destination, so
+/// nearby origins and destinations share the part of their path along the
+/// geograph.
+///
+/// A cache must only be used with the geograph it was filled from, which
+/// files it is saved to record, so that loading it for another geograph fails.
+#[derive(Debug)]
+pub struct RouteCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl RouteCache {
+    /// Creates an empty cache, keeping up to `capacity` paths.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Loads a cache saved with `save` for the same geograph, keeping up to
+    /// `capacity` of its most recently used paths. Statistics start from zero.
+    pub fn load(
+        file: impl AsRef<std::path::Path>,
+        geograph: &Geograph,
+        capacity: usize,
+    ) -> io::Result<Self> {
+        let cache = Self::new(capacity);
+        let contents = fs::read_to_string(file)?;
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(invalid_data("Not a route cache file"));
+        }
+        if lines.next() != Some(identity(geograph).as_str()) {
+            return Err(invalid_data("Route cache saved for another geograph"));
+        }
+        for line in lines {
+            let (endpoints, path) = parse_line(line).ok_or_else(|| invalid_data(line))?;
+            cache.insert(endpoints, path);
+        }
+        Ok(cache)
+    }
+
+    /// Saves the paths of the cache filled from a geograph to a file, to be
+    /// loaded with `load`, such as after a restart. The file is replaced at
+    /// once, so that it is never left half written.
+    pub fn save(&self, file: impl AsRef<std::path::Path>, geograph: &Geograph) -> io::Result<()> {
+        let entries = self.lock();
+        let mut contents = format!("{HEADER}\n{}\n", identity(geograph));
+        // Least recently used first, so that loading keeps the same order
+        for endpoints in entries.recency.values() {
+            let (path, _) = &entries.paths[endpoints];
+            contents.push_str(&format!("{} {}", endpoints.0, endpoints.1));
+            match path {
+                Some(path) => {
+                    for (lat, lng) in path.iter() {
+                        contents.push_str(&format!(" {lat},{lng}"));
+                    }
+                }
+                None => contents.push_str(" -"),
+            }
+            contents.push('\n');
+        }
+        drop(entries);
+
+        let file = file.as_ref();
+        let mut temporary = file.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, contents)?;
+        fs::rename(&temporary, file)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.lock();
+        CacheStats {
+            hits: entries.hits,
+            misses: entries.misses,
+            entries: entries.paths.len(),
+        }
+    }
+
+    /// Removes all the paths and resets the statistics.
+    pub fn clear(&self) {
+        *self.lock() = Entries::default();
+    }
+
+    /// Looks up the path between two nodes, counting a hit or a miss.
+    fn get(&self, endpoints: Endpoints) -> Option<CachedPath> {
+        let mut entries = self.lock();
+        let Some((path, used)) = entries.paths.get(&endpoints).cloned() else {
+            entries.misses += 1;
+            return None;
+        };
+        entries.hits += 1;
+        entries.recency.remove(&used);
+        let used = entries.touch(endpoints);
+        entries.paths.insert(endpoints, (path.clone(), used));
+        Some(path)
+    }
+
+    /// Adds the path between two nodes, evicting the least recently used
+    /// paths beyond the capacity.
+    fn insert(&self, endpoints: Endpoints, path: CachedPath) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.lock();
+        let used = entries.touch(endpoints);
+        if let Some((_, previous)) = entries.paths.insert(endpoints, (path, used)) {
+            entries.recency.remove(&previous);
+        }
+        while entries.paths.len() > self.capacity {
+            let Some((_, oldest)) = entries.recency.pop_first() else {
+                break;
+            };
+            entries.paths.remove(&oldest);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Second line of the files caches are saved to, identifying the geograph
+/// they were filled from by its name and its number of nodes and edges.
+fn identity(geograph: &Geograph) -> String {
+    let edges: usize = geograph.iter_nodes().map(|node| node.waypoints.len()).sum();
+    format!("{} {} {}", geograph.len(), edges, geograph.name)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Parses a line of a cache file: the ids of the endpoints, followed by
+/// the geolocations of the path, or `-` if there is none.
+fn parse_line(line: &str) -> Option<(Endpoints, CachedPath)> {
+    let mut fields = line.split(' ');
+    let entry = fields.next()?.parse().ok()?;
+    let exit = fields.next()?.parse().ok()?;
+    let fields: Vec<&str> = fields.collect();
+    let path = match fields.as_slice() {
+        ["-"] => None,
+        points => Some(
+            points
+                .iter()
+                .map(|point| {
+                    let (lat, lng) = point.split_once(',')?;
+                    Some((lat.parse().ok()?, lng.parse().ok()?))
+                })
+                .collect::<Option<Arc<[Geoloc]>>>()?,
+        ),
+    };
+    Some(((entry, exit), path))
+}
+
+impl Geograph {
+    /// Calculates the distance between two geolocations like `shortest_path`,
+    /// reusing the part of the path along the geograph cached for the nodes
+    /// closest to the origin and destination. On a miss, the path is searched
+    /// and added to the cache, even when the destination is not reachable.
+    pub fn shortest_path_cached(
+        &self,
+        origin: &impl Geolocalizable,
+        destination: &impl Geolocalizable,
+        cache: &RouteCache,
+    ) -> ShortestPath {
+        let options = RouteOptions::default();
+        let query = Query::new(self, &options);
+        let allowed = |node: &Node| query.allows_node(node);
+        let (Some(entry), Some(exit)) = (
+            self.closest_where(origin, allowed),
+            self.closest_where(destination, allowed),
+        ) else {
+            return Route::direct(origin.geoloc(), destination.geoloc()).into();
+        };
+
+        let endpoints = (entry.id, exit.id);
+        let cached = cache.get(endpoints).unwrap_or_else(|| {
+            let labels = self.dijsktra(entry.id, exit.id, 0.0, &query).ok().flatten();
+            let path: CachedPath = labels.map(|labels| {
+                let route = self.route_via(origin, destination, labels, 0.0, &query);
+                route.path[1..route.path.len() - 1].into()
+            });
+            cache.insert(endpoints, path.clone());
+            path
+        });
+
+        match cached {
+            Some(cached) => {
+                let mut path = Vec::with_capacity(cached.len() + 2);
+                path.push(origin.geoloc());
+                path.extend(cached.iter().copied());
+                path.push(destination.geoloc());
+                let path = Path::from(path);
+                (path.length(), path, PathType::ViaWaypoints)
+            }
+            None => Route::direct(origin.geoloc(), destination.geoloc()).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geograph_fixture() -> Geograph {
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (0.0, 0.0), vec![1]))
+            .add(Node::new(1, (1.0, 1.0), vec![0, 2, 3, 4]))
+            .add(Node::new(2, (2.0, 2.0), vec![1, 3, 5]))
+            .add(Node::new(3, (3.0, 3.0), vec![1, 2, 3, 4, 5]))
+            .add(Node::new(4, (4.0, 4.0), vec![1, 3, 4, 5]))
+            .add(Node::new(5, (5.0, 5.0), vec![3, 4]))
+            .add(Node::new(6, (-5.0, -5.0), vec![]));
+        geograph
+    }
+
+    fn assert_same_path(actual: ShortestPath, expected: ShortestPath) {
+        assert_eq!(actual.0.km(), expected.0.km());
+        assert_eq!(*actual.1, *expected.1);
+        assert_eq!(actual.2, expected.2);
+    }
+
+    #[test]
+    fn test_shortest_path_cached() {
+        let geograph = geograph_fixture();
+        let cache = RouteCache::new(10);
+        let (from, to) = ((0.1, 0.0), (5.0, 5.2));
+
+        for _ in 0..2 {
+            assert_same_path(
+                geograph.shortest_path_cached(&from, &to, &cache),
+                geograph.shortest_path(&from, &to),
+            );
+        }
+        // Nearby origins snap to the same node
+        assert_same_path(
+            geograph.shortest_path_cached(&(0.0, 0.1), &to, &cache),
+            geograph.shortest_path(&(0.0, 0.1), &to),
+        );
+
+        let unreachable = geograph.shortest_path_cached(&from, &(-5.0, -5.0), &cache);
+        assert_eq!(unreachable.2, PathType::Direct);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 2, 2));
+        assert_eq!(stats.hit_rate(), 0.5);
+
+        cache.clear();
+        assert_eq!(cache.stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn test_least_recently_used_eviction() {
+        let geograph = geograph_fixture();
+        let cache = RouteCache::new(2);
+        let (a, b, c) = ((0.0, 0.0), (3.0, 3.0), (5.0, 5.0));
+
+        geograph.shortest_path_cached(&a, &b, &cache);
+        geograph.shortest_path_cached(&a, &c, &cache);
+        geograph.shortest_path_cached(&a, &b, &cache);
+        // Evicts the path from a to c, used least recently
+        geograph.shortest_path_cached(&b, &c, &cache);
+        assert_eq!(cache.stats().entries, 2);
+        geograph.shortest_path_cached(&a, &b, &cache);
+        geograph.shortest_path_cached(&a, &c, &cache);
+        assert_eq!(cache.stats().hits, 2);
+        assert_eq!(cache.stats().misses, 4);
+    }
+
+    #[test]
+    fn test_persistence() {
+        let geograph = geograph_fixture();
+        let cache = RouteCache::new(10);
+        let (from, to) = ((0.1, 0.0), (5.0, 5.2));
+        geograph.shortest_path_cached(&from, &to, &cache);
+        geograph.shortest_path_cached(&from, &(-5.0, -5.0), &cache);
+
+        let file = std::env::temp_dir().join(format!("routrs-cache-{}", std::process::id()));
+        cache.save(&file, &geograph).unwrap();
+        let loaded = RouteCache::load(&file, &geograph, 10).unwrap();
+
+        // Caches filled from other geographs are rejected
+        let mut other = geograph_fixture();
+        other.add(Node::new(7, (-6.0, -6.0), vec![]));
+        let error = RouteCache::load(&file, &other, 10).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        other = geograph_fixture();
+        other.name = "Other Geograph".to_string();
+        assert!(RouteCache::load(&file, &other, 10).is_err());
+        fs::remove_file(&file).unwrap();
+
+        assert_eq!(loaded.stats().entries, 2);
+        assert_same_path(
+            geograph.shortest_path_cached(&from, &to, &loaded),
+            geograph.shortest_path(&from, &to),
+        );
+        let unreachable = geograph.shortest_path_cached(&from, &(-5.0, -5.0), &loaded);
+        assert_eq!(unreachable.2, PathType::Direct);
+        assert_eq!(loaded.stats().hits, 2);
+
+        assert!(parse_line("1 2 0.5,x").is_none());
+        assert_eq!(parse_line("1 2 -"), Some(((1, 2), None)));
+    }
+}
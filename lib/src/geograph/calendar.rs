@@ -0,0 +1,336 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Represents the errors of an invalid date.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DateError {
+    /// The month or day is out of range, such as `2023-02-29`.
+    OutOfRange,
+    /// The text is not formatted as `YYYY-MM-DD` or `MM-DD`.
+    Malformed(String),
+}
+
+impl fmt::Display for DateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateError::OutOfRange => write!(f, "month or day out of range"),
+            DateError::Malformed(text) => write!(f, "malformed date {text:?}"),
+        }
+    }
+}
+
+impl std::error::Error for DateError {}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: Option<i32>, month: u8) -> u8 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        // Without a year, February 29th is valid, as it exists on leap years
+        2 if year.is_none_or(is_leap_year) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+pub(crate) const SECONDS_PER_HOUR: f64 = 3_600.0;
+pub(crate) const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Seconds elapsed since the Unix epoch, negative for earlier times.
+pub(crate) fn epoch_seconds(time: SystemTime) -> f64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs_f64(),
+        Err(error) => -error.duration().as_secs_f64(),
+    }
+}
+
+/// Point in time from the seconds elapsed since the Unix epoch.
+pub(crate) fn from_epoch_seconds(seconds: f64) -> SystemTime {
+    match Duration::try_from_secs_f64(seconds.abs()) {
+        Ok(elapsed) if seconds >= 0.0 => UNIX_EPOCH + elapsed,
+        Ok(elapsed) => UNIX_EPOCH - elapsed,
+        Err(_) => UNIX_EPOCH,
+    }
+}
+
+fn parse_number<T: FromStr>(text: &str, digits: usize, whole: &str) -> Result<T, DateError> {
+    if text.len() != digits || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(DateError::Malformed(whole.to_string()));
+    }
+    text.parse()
+        .map_err(|_| DateError::Malformed(whole.to_string()))
+}
+
+/// Represents a day of the year, recurring every year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MonthDay {
+    month: u8,
+    day: u8,
+}
+
+impl MonthDay {
+    pub fn new(month: u8, day: u8) -> Result<Self, DateError> {
+        if !(1..=12).contains(&month) || !(1..=days_in_month(None, month)).contains(&day) {
+            return Err(DateError::OutOfRange);
+        }
+        Ok(Self { month, day })
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl FromStr for MonthDay {
+    type Err = DateError;
+
+    /// Parses a `MM-DD` day of the year.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (month, day) = text
+            .split_once('-')
+            .ok_or_else(|| DateError::Malformed(text.to_string()))?;
+        MonthDay::new(parse_number(month, 2, text)?, parse_number(day, 2, text)?)
+    }
+}
+
+impl fmt::Display for MonthDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}-{:02}", self.month, self.day)
+    }
+}
+
+/// Represents a calendar date, such as the departure date of a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u8, day: u8) -> Result<Self, DateError> {
+        if !(1..=12).contains(&month) || !(1..=days_in_month(Some(year), month)).contains(&day) {
+            return Err(DateError::OutOfRange);
+        }
+        Ok(Self { year, month, day })
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn month_day(&self) -> MonthDay {
+        MonthDay {
+            month: self.month,
+            day: self.day,
+        }
+    }
+}
+
+/// UTC date of a point in time.
+impl From<SystemTime> for Date {
+    fn from(time: SystemTime) -> Self {
+        // Converts the days since the epoch into a proleptic Gregorian date,
+        // from the algorithm by Howard Hinnant
+        let days = (epoch_seconds(time) / SECONDS_PER_DAY).floor() as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u8;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Self {
+            year: year as i32,
+            month,
+            day,
+        }
+    }
+}
+
+impl FromStr for Date {
+    type Err = DateError;
+
+    /// Parses a `YYYY-MM-DD` date.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let malformed = || DateError::Malformed(text.to_string());
+        let (year, month_day) = text.split_once('-').ok_or_else(malformed)?;
+        let (month, day) = month_day.split_once('-').ok_or_else(malformed)?;
+        Date::new(
+            parse_number(year, 4, text)?,
+            parse_number(month, 2, text)?,
+            parse_number(day, 2, text)?,
+        )
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Represents the period during which a node or edge is open, both ends included.
+///
+/// Serialized as `{"start": "07-01", "end": "10-31"}` for yearly windows, and
+/// as `{"start": "2024-12-20", "end": "2025-01-05"}` for windows between dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "RawValidityWindow", into = "RawValidityWindow")
+)]
+pub enum ValidityWindow {
+    /// Recurs every year, wrapping around the new year if it ends before it starts,
+    /// such as a winter ferry link from November to March.
+    Yearly { start: MonthDay, end: MonthDay },
+    /// Between two dates.
+    Dates { start: Date, end: Date },
+}
+
+impl ValidityWindow {
+    pub fn yearly(start: MonthDay, end: MonthDay) -> Self {
+        ValidityWindow::Yearly { start, end }
+    }
+
+    pub fn dates(start: Date, end: Date) -> Self {
+        ValidityWindow::Dates { start, end }
+    }
+
+    pub fn contains(&self, date: Date) -> bool {
+        match *self {
+            ValidityWindow::Yearly { start, end } if start <= end => {
+                (start..=end).contains(&date.month_day())
+            }
+            ValidityWindow::Yearly { start, end } => {
+                date.month_day() >= start || date.month_day() <= end
+            }
+            ValidityWindow::Dates { start, end } => (start..=end).contains(&date),
+        }
+    }
+
+    /// Checks if a date is within any of the windows.
+    /// Nodes and edges without windows are always open.
+    pub fn any_contains(windows: &[ValidityWindow], date: Date) -> bool {
+        windows.is_empty() || windows.iter().any(|window| window.contains(date))
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct RawValidityWindow {
+    start: String,
+    end: String,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawValidityWindow> for ValidityWindow {
+    type Error = DateError;
+
+    fn try_from(raw: RawValidityWindow) -> Result<Self, Self::Error> {
+        match (raw.start.parse(), raw.end.parse()) {
+            (Ok(start), Ok(end)) => Ok(ValidityWindow::Dates { start, end }),
+            _ => Ok(ValidityWindow::Yearly {
+                start: raw.start.parse()?,
+                end: raw.end.parse()?,
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<ValidityWindow> for RawValidityWindow {
+    fn from(window: ValidityWindow) -> Self {
+        let (start, end) = match window {
+            ValidityWindow::Yearly { start, end } => (start.to_string(), end.to_string()),
+            ValidityWindow::Dates { start, end } => (start.to_string(), end.to_string()),
+        };
+        RawValidityWindow { start, end }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> Date {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(Date::new(2024, 2, 29).unwrap().to_string(), "2024-02-29");
+        assert_eq!(Date::new(2023, 2, 29), Err(DateError::OutOfRange));
+        assert_eq!(Date::new(2023, 13, 1), Err(DateError::OutOfRange));
+        assert!(MonthDay::new(2, 29).is_ok());
+        assert_eq!("07-01".parse(), MonthDay::new(7, 1));
+        assert_eq!(
+            "2024-7-1".parse::<Date>(),
+            Err(DateError::Malformed("2024-7-1".to_string()))
+        );
+        assert!(date("2023-12-31") < date("2024-01-01"));
+    }
+
+    #[test]
+    fn test_dates_from_time() {
+        let at = |seconds: u64| Date::from(UNIX_EPOCH + Duration::from_secs(seconds));
+        assert_eq!(at(0), date("1970-01-01"));
+        assert_eq!(at(951_782_400), date("2000-02-29"));
+        assert_eq!(at(1_735_689_599), date("2024-12-31"));
+        assert_eq!(at(1_735_689_600), date("2025-01-01"));
+        assert_eq!(
+            Date::from(UNIX_EPOCH - Duration::from_secs(1)),
+            date("1969-12-31")
+        );
+    }
+
+    #[test]
+    fn test_windows() {
+        let summer = ValidityWindow::yearly("07-01".parse().unwrap(), "10-31".parse().unwrap());
+        let winter = ValidityWindow::yearly("11-01".parse().unwrap(), "03-31".parse().unwrap());
+        let holidays = ValidityWindow::dates(date("2024-12-20"), date("2025-01-05"));
+
+        assert!(summer.contains(date("2024-07-01")));
+        assert!(summer.contains(date("2024-10-31")));
+        assert!(!summer.contains(date("2024-11-01")));
+        assert!(winter.contains(date("2024-12-25")));
+        assert!(winter.contains(date("2025-02-01")));
+        assert!(!winter.contains(date("2025-06-01")));
+        assert!(holidays.contains(date("2025-01-01")));
+        assert!(!holidays.contains(date("2026-01-01")));
+
+        assert!(ValidityWindow::any_contains(&[], date("2025-06-01")));
+        assert!(ValidityWindow::any_contains(
+            &[summer, winter],
+            date("2025-01-01")
+        ));
+        assert!(!ValidityWindow::any_contains(
+            &[summer, holidays],
+            date("2025-06-01")
+        ));
+    }
+}
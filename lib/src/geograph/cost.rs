@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use super::{EdgeAttributes, Geolocalizable, Node};
+
+/// A trait for the cost of traversing an edge, which routing minimizes.
+///
+/// It's evaluated for every edge relaxed during the search, from a node to one
+/// of its waypoints, along with the edge attributes if any were set.
+/// Returning `None` makes the edge impassable. Costs must be non-negative:
+/// negative or non-finite costs are treated as impassable edges.
+pub trait EdgeCost {
+    fn cost(&self, from: &Node, to: &Node, edge: Option<&EdgeAttributes>) -> Option<f64>;
+}
+
+impl<F> EdgeCost for F
+where
+    F: Fn(&Node, &Node, Option<&EdgeAttributes>) -> Option<f64>,
+{
+    fn cost(&self, from: &Node, to: &Node, edge: Option<&EdgeAttributes>) -> Option<f64> {
+        self(from, to, edge)
+    }
+}
+
+/// Haversine distance between the nodes, in kilometres.
+/// This is the default cost, which finds the shortest path.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Haversine;
+
+impl EdgeCost for Haversine {
+    fn cost(&self, from: &Node, to: &Node, _: Option<&EdgeAttributes>) -> Option<f64> {
+        Some(from.haversine(to))
+    }
+}
+
+/// Custom edge weight when set, or the haversine distance between the nodes
+/// otherwise.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Weighted;
+
+impl EdgeCost for Weighted {
+    fn cost(&self, from: &Node, to: &Node, edge: Option<&EdgeAttributes>) -> Option<f64> {
+        edge.and_then(|edge| edge.weight)
+            .or_else(|| Haversine.cost(from, to, edge))
+    }
+}
+
+/// Number of edges traversed, one per edge, to minimize the hops of a route.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hops;
+
+impl EdgeCost for Hops {
+    fn cost(&self, _: &Node, _: &Node, _: Option<&EdgeAttributes>) -> Option<f64> {
+        Some(1.0)
+    }
+}
+
+/// Multiplies the cost of edges with the given tags by a penalty factor.
+/// When an edge has several penalized tags, all their factors are applied.
+#[derive(Debug, Clone)]
+pub struct TagPenalty<C: EdgeCost> {
+    inner: C,
+    penalties: HashMap<String, f64>,
+}
+
+impl<C: EdgeCost> TagPenalty<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            penalties: HashMap::new(),
+        }
+    }
+
+    pub fn with(mut self, tag: &str, factor: f64) -> Self {
+        self.penalties.insert(tag.to_string(), factor);
+        self
+    }
+}
+
+impl<C: EdgeCost> EdgeCost for TagPenalty<C> {
+    fn cost(&self, from: &Node, to: &Node, edge: Option<&EdgeAttributes>) -> Option<f64> {
+        let cost = self.inner.cost(from, to, edge)?;
+        let factor: f64 = edge
+            .map(|edge| {
+                edge.tags
+                    .iter()
+                    .filter_map(|tag| self.penalties.get(tag))
+                    .product()
+            })
+            .unwrap_or(1.0);
+        Some(cost * factor)
+    }
+}
+
+/// Total cost of reaching a node, ordered to be used as the priority
+/// of the search queue.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) struct TotalCost(pub f64);
+
+impl Eq for TotalCost {}
+impl PartialOrd for TotalCost {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for TotalCost {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.partial_cmp(&other.0).unwrap()
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const METERS_PER_KM: f64 = 1_000.0;
+pub(crate) const KM_PER_MILE: f64 = 1.609_344;
+pub(crate) const KM_PER_NAUTICAL_MILE: f64 = 1.852;
+
+/// Represents a distance between two points on the Earth's surface.
+///
+/// Distances are stored in kilometres, but can be created from and read as
+/// metres, statute miles or nautical miles, so callers never need to convert
+/// units by hand.
+///
+/// When serialized, a distance is represented as a number of kilometres.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct Distance(f64);
+
+impl Distance {
+    pub const ZERO: Distance = Distance(0.0);
+    pub const INFINITY: Distance = Distance(f64::INFINITY);
+
+    pub fn from_km(km: f64) -> Self {
+        Self(km)
+    }
+
+    pub fn from_meters(meters: f64) -> Self {
+        Self(meters / METERS_PER_KM)
+    }
+
+    pub fn from_miles(miles: f64) -> Self {
+        Self(miles * KM_PER_MILE)
+    }
+
+    pub fn from_nautical_miles(nautical_miles: f64) -> Self {
+        Self(nautical_miles * KM_PER_NAUTICAL_MILE)
+    }
+
+    pub fn km(&self) -> f64 {
+        self.0
+    }
+
+    pub fn meters(&self) -> f64 {
+        self.0 * METERS_PER_KM
+    }
+
+    pub fn miles(&self) -> f64 {
+        self.0 / KM_PER_MILE
+    }
+
+    pub fn nautical_miles(&self) -> f64 {
+        self.0 / KM_PER_NAUTICAL_MILE
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.0.is_finite()
+    }
+}
+
+impl Eq for Distance {}
+impl PartialOrd for Distance {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+/// Distances are totally ordered, with `NaN` after infinity, so that
+/// comparing them never panics.
+impl Ord for Distance {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl fmt::Display for Distance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)?;
+        f.write_str(" km")
+    }
+}
+
+impl Add for Distance {
+    type Output = Distance;
+
+    fn add(self, other: Distance) -> Distance {
+        Distance(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Distance {
+    fn add_assign(&mut self, other: Distance) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Distance {
+    type Output = Distance;
+
+    fn sub(self, other: Distance) -> Distance {
+        Distance(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Distance {
+    fn sub_assign(&mut self, other: Distance) {
+        self.0 -= other.0;
+    }
+}
+
+impl Mul<f64> for Distance {
+    type Output = Distance;
+
+    fn mul(self, factor: f64) -> Distance {
+        Distance(self.0 * factor)
+    }
+}
+
+impl Div<f64> for Distance {
+    type Output = Distance;
+
+    fn div(self, divisor: f64) -> Distance {
+        Distance(self.0 / divisor)
+    }
+}
+
+// Dividing two distances yields a unitless ratio
+impl Div for Distance {
+    type Output = f64;
+
+    fn div(self, other: Distance) -> f64 {
+        self.0 / other.0
+    }
+}
+
+impl Sum for Distance {
+    fn sum<I: Iterator<Item = Distance>>(iter: I) -> Self {
+        iter.fold(Distance::ZERO, |total, distance| total + distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_conversions() {
+        let distance = Distance::from_nautical_miles(1.0);
+
+        assert_eq!(distance.km(), 1.852);
+        assert_eq!(distance.meters(), 1852.0);
+        assert_eq!(Distance::from_meters(1500.0).km(), 1.5);
+        assert_eq!(Distance::from_miles(1.0).km(), 1.609344);
+        assert!((Distance::from_km(1.609344).miles() - 1.0).abs() < 1e-12);
+        assert!((Distance::from_km(1.852).nautical_miles() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut distances = [
+            Distance::from_km(f64::NAN),
+            Distance::from_km(2.0),
+            Distance::INFINITY,
+            Distance::from_km(1.0),
+        ];
+        distances.sort();
+
+        assert_eq!(distances[0], Distance::from_km(1.0));
+        assert_eq!(distances[2], Distance::INFINITY);
+        assert!(!distances[3].km().is_finite());
+        assert!(Distance::from_km(1.0) < Distance::from_km(2.0));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let mut distance = Distance::from_km(10.0) + Distance::from_km(5.0);
+        assert_eq!(distance, Distance::from_km(15.0));
+
+        distance -= Distance::from_km(3.0);
+        assert_eq!(distance * 2.0, Distance::from_km(24.0));
+        assert_eq!(distance / 4.0, Distance::from_km(3.0));
+        assert_eq!(distance / Distance::from_km(6.0), 2.0);
+
+        let total: Distance = (1..=3).map(|km| Distance::from_km(km as f64)).sum();
+        assert_eq!(total, Distance::from_km(6.0));
+    }
+
+    #[test]
+    fn test_ordering_and_display() {
+        assert!(Distance::ZERO < Distance::from_meters(1.0));
+        assert!(Distance::from_km(1e9) < Distance::INFINITY);
+        assert_eq!(Distance::from_km(12.5).to_string(), "12.5 km");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_serializes_as_km() {
+        let json = serde_json::to_string(&Distance::from_nautical_miles(10.0)).unwrap();
+        assert_eq!(json, "18.52");
+
+        let distance: Distance = serde_json::from_str("18.52").unwrap();
+        assert_eq!(distance, Distance::from_km(18.52));
+    }
+}
@@ -0,0 +1,28 @@
+use super::{EdgeLimits, Geoloc, TravelTimeFunction, ValidityWindow};
+
+/// Represents the optional attributes of a directed connection
+/// between a node and one of its waypoints.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EdgeAttributes {
+    /// Custom weight of the edge, for networks where the cost of traversing
+    /// it is not the straight-line distance between its nodes.
+    pub weight: Option<f64>,
+    /// Free-form tags, such as `ferry` or `toll`.
+    pub tags: Vec<String>,
+    /// Shape of the edge between its nodes, if it is not a straight line.
+    pub geometry: Option<Vec<Geoloc>>,
+    /// Largest vessel the edge can take, such as the locks of a canal.
+    pub limits: Option<EdgeLimits>,
+    /// Periods during which the edge is open, such as the season of a ferry
+    /// link. Edges without windows are always open.
+    pub validity: Vec<ValidityWindow>,
+    /// Travel time depending on the time the edge is entered, used by the
+    /// routes with a departure time instead of the speed profile.
+    pub travel_time: Option<TravelTimeFunction>,
+}
+
+impl EdgeAttributes {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
@@ -1,3 +1,10 @@
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Distance;
+
 const EARTH_RADIUS_KM: f64 = 6_371.0;
 
 /// Represents a geographic coordinate.
@@ -8,6 +15,8 @@ pub type Lat = Coord;
 pub type Lng = Coord;
 
 /// Represents a geographic location with latitude and longitude coordinates.
+/// It is not validated: see `LatLng` and `LngLat` for range-checked locations
+/// with an explicit coordinate order.
 pub type Geoloc = (Lat, Lng);
 
 /// A trait for types that can be geolocalized and have geographic coordinates.
@@ -21,7 +30,7 @@ pub trait Geolocalizable {
         self.geoloc().1
     }
 
-    /// Calculate the Haversine distance between two geographic coordinates
+    /// Calculate the Haversine distance between two geographic coordinates, in kilometres
     fn haversine(&self, destination: &impl Geolocalizable) -> f64 {
         let lat1 = self.lat().to_radians();
         let lng1 = self.lng().to_radians();
@@ -38,6 +47,39 @@ pub trait Geolocalizable {
 
         EARTH_RADIUS_KM * c
     }
+
+    /// Calculate the initial bearing of the great circle going to the destination,
+    /// in degrees clockwise from the north, within [0, 360).
+    fn bearing(&self, destination: &impl Geolocalizable) -> f64 {
+        let lat1 = self.lat().to_radians();
+        let lat2 = destination.lat().to_radians();
+        let d_lng = (destination.lng() - self.lng()).to_radians();
+
+        let y = d_lng.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lng.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// Calculate the location at the given fraction of the great circle going
+    /// to the destination, from 0 at this location to 1 at the destination.
+    fn interpolate(&self, destination: &impl Geolocalizable, fraction: f64) -> Geoloc {
+        let angle = self.haversine(destination) / EARTH_RADIUS_KM;
+        if angle < f64::EPSILON {
+            return self.geoloc();
+        }
+        let (lat1, lng1) = (self.lat().to_radians(), self.lng().to_radians());
+        let (lat2, lng2) = (
+            destination.lat().to_radians(),
+            destination.lng().to_radians(),
+        );
+        let a = ((1.0 - fraction) * angle).sin() / angle.sin();
+        let b = (fraction * angle).sin() / angle.sin();
+
+        let x = a * lat1.cos() * lng1.cos() + b * lat2.cos() * lng2.cos();
+        let y = a * lat1.cos() * lng1.sin() + b * lat2.cos() * lng2.sin();
+        let z = a * lat1.sin() + b * lat2.sin();
+        (z.atan2(x.hypot(y)).to_degrees(), y.atan2(x).to_degrees())
+    }
 }
 
 impl Geolocalizable for Geoloc {
@@ -46,18 +88,147 @@ impl Geolocalizable for Geoloc {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub struct Distance(pub f64);
+/// Errors raised when building a validated coordinate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeolocError {
+    /// The coordinate is NaN or infinite.
+    NotFinite(Coord),
+    /// The latitude is outside of the [-90, 90] range.
+    LatitudeOutOfRange(Lat),
+}
 
-impl Eq for Distance {}
-impl PartialOrd for Distance {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl fmt::Display for GeolocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeolocError::NotFinite(coord) => write!(f, "coordinate {coord} is not finite"),
+            GeolocError::LatitudeOutOfRange(lat) => {
+                write!(f, "latitude {lat} is outside of the [-90, 90] range")
+            }
+        }
     }
 }
-impl Ord for Distance {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.0.partial_cmp(&other.0).unwrap()
+
+impl std::error::Error for GeolocError {}
+
+/// Validates a latitude and longitude pair, normalizing the longitude
+/// to the [-180, 180) range.
+fn validate(lat: Lat, lng: Lng) -> Result<Geoloc, GeolocError> {
+    if let Some(&coord) = [lat, lng].iter().find(|coord| !coord.is_finite()) {
+        return Err(GeolocError::NotFinite(coord));
+    }
+    if !(-90.0..=90.0).contains(&lat) {
+        return Err(GeolocError::LatitudeOutOfRange(lat));
+    }
+
+    // Only wrap out-of-range longitudes, so that valid ones are kept exact
+    if (-180.0..180.0).contains(&lng) {
+        Ok((lat, lng))
+    } else {
+        Ok((lat, (lng + 180.0).rem_euclid(360.0) - 180.0))
+    }
+}
+
+/// A validated geographic location, ordered as latitude first.
+/// Latitudes are checked to be within [-90, 90] and longitudes are
+/// normalized to [-180, 180).
+///
+/// When serialized, it is represented as a `[lat, lng]` array.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "(Lat, Lng)", into = "(Lat, Lng)")
+)]
+pub struct LatLng {
+    lat: Lat,
+    lng: Lng,
+}
+
+impl LatLng {
+    pub fn new(lat: Lat, lng: Lng) -> Result<Self, GeolocError> {
+        let (lat, lng) = validate(lat, lng)?;
+        Ok(Self { lat, lng })
+    }
+}
+
+impl Geolocalizable for LatLng {
+    fn geoloc(&self) -> Geoloc {
+        (self.lat, self.lng)
+    }
+}
+
+impl TryFrom<(Lat, Lng)> for LatLng {
+    type Error = GeolocError;
+
+    fn try_from((lat, lng): (Lat, Lng)) -> Result<Self, Self::Error> {
+        Self::new(lat, lng)
+    }
+}
+
+impl From<LatLng> for (Lat, Lng) {
+    fn from(latlng: LatLng) -> Self {
+        (latlng.lat, latlng.lng)
+    }
+}
+
+impl From<LngLat> for LatLng {
+    fn from(lnglat: LngLat) -> Self {
+        Self {
+            lat: lnglat.lat,
+            lng: lnglat.lng,
+        }
+    }
+}
+
+/// A validated geographic location, ordered as longitude first, as used by
+/// GeoJSON. Latitudes are checked to be within [-90, 90] and longitudes are
+/// normalized to [-180, 180).
+///
+/// When serialized, it is represented as a `[lng, lat]` array.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "(Lng, Lat)", into = "(Lng, Lat)")
+)]
+pub struct LngLat {
+    lng: Lng,
+    lat: Lat,
+}
+
+impl LngLat {
+    pub fn new(lng: Lng, lat: Lat) -> Result<Self, GeolocError> {
+        let (lat, lng) = validate(lat, lng)?;
+        Ok(Self { lng, lat })
+    }
+}
+
+impl Geolocalizable for LngLat {
+    fn geoloc(&self) -> Geoloc {
+        (self.lat, self.lng)
+    }
+}
+
+impl TryFrom<(Lng, Lat)> for LngLat {
+    type Error = GeolocError;
+
+    fn try_from((lng, lat): (Lng, Lat)) -> Result<Self, Self::Error> {
+        Self::new(lng, lat)
+    }
+}
+
+impl From<LngLat> for (Lng, Lat) {
+    fn from(lnglat: LngLat) -> Self {
+        (lnglat.lng, lnglat.lat)
+    }
+}
+
+impl From<LatLng> for LngLat {
+    fn from(latlng: LatLng) -> Self {
+        Self {
+            lng: latlng.lng,
+            lat: latlng.lat,
+        }
     }
 }
 
@@ -90,11 +261,13 @@ impl<T: Geolocalizable> From<Vec<T>> for Path<T> {
 }
 
 impl<T: Geolocalizable> Path<T> {
-    pub fn length(&self) -> f64 {
-        self.iter()
-            .zip(self.iter().skip(1))
-            .map(|(node, next)| node.haversine(next))
-            .sum()
+    pub fn length(&self) -> Distance {
+        Distance::from_km(
+            self.iter()
+                .zip(self.iter().skip(1))
+                .map(|(node, next)| node.haversine(next))
+                .sum(),
+        )
     }
 }
 #[cfg(test)]
@@ -119,5 +292,84 @@ mod tests {
 
             assert_eq!(a.haversine(&b), 314.4029510236249);
         }
+
+        #[test]
+        fn test_bearing() {
+            let origin = (0.0, 0.0);
+
+            assert_eq!(origin.bearing(&(1.0, 0.0)), 0.0);
+            assert_eq!(origin.bearing(&(0.0, 1.0)), 90.0);
+            assert_eq!(origin.bearing(&(-1.0, 0.0)), 180.0);
+            assert_eq!(origin.bearing(&(0.0, -1.0)), 270.0);
+            // Across the antimeridian, heading east
+            assert!((((0.0, 179.5).bearing(&(0.0, -179.5))) - 90.0).abs() < 1e-9);
+        }
+
+        #[test]
+        fn test_great_circle_interpolation() {
+            let (from, to) = ((40.6759, -74.0504), (51.95, 4.05));
+            let midpoint = from.interpolate(&to, 0.5);
+
+            assert!(from.haversine(&from.interpolate(&to, 0.0)) < 1e-9);
+            assert!(to.haversine(&from.interpolate(&to, 1.0)) < 1e-9);
+            assert!((midpoint.haversine(&from) - midpoint.haversine(&to)).abs() < 1e-6);
+            // The great circle bulges north of the straight line between the coordinates
+            assert!(midpoint.0 > (from.0 + to.0) / 2.0 + 5.0);
+
+            let across = (10.0, 179.0).interpolate(&(10.0, -179.0), 0.5);
+            assert!((across.1.abs() - 180.0).abs() < 1e-9);
+        }
+    }
+
+    mod validated {
+        use super::*;
+
+        #[test]
+        fn test_coordinate_order() {
+            let latlng = LatLng::new(51.3, 179.5).unwrap();
+            let lnglat = LngLat::new(179.5, 51.3).unwrap();
+
+            assert_eq!(latlng.geoloc(), (51.3, 179.5));
+            assert_eq!(lnglat.geoloc(), (51.3, 179.5));
+            assert_eq!(LatLng::from(lnglat), latlng);
+            assert_eq!(<(Lng, Lat)>::from(lnglat), (179.5, 51.3));
+        }
+
+        #[test]
+        fn test_rejects_invalid_coordinates() {
+            assert_eq!(
+                LatLng::new(179.5, 51.3),
+                Err(GeolocError::LatitudeOutOfRange(179.5))
+            );
+            assert!(matches!(
+                LngLat::new(f64::NAN, 0.0),
+                Err(GeolocError::NotFinite(_))
+            ));
+            assert_eq!(
+                LatLng::new(0.0, f64::INFINITY),
+                Err(GeolocError::NotFinite(f64::INFINITY))
+            );
+        }
+
+        #[test]
+        fn test_normalizes_longitude() {
+            assert_eq!(LatLng::new(0.0, 180.0).unwrap().lng(), -180.0);
+            assert_eq!(LatLng::new(0.0, 190.0).unwrap().lng(), -170.0);
+            assert_eq!(LngLat::new(-190.0, 0.0).unwrap().lng(), 170.0);
+            assert_eq!(LngLat::new(-180.0, 0.0).unwrap().lng(), -180.0);
+            assert_eq!(LngLat::new(10.2, 0.0).unwrap().lng(), 10.2);
+        }
+
+        #[cfg(feature = "json")]
+        #[test]
+        fn test_serde_order() {
+            let lnglat: LngLat = serde_json::from_str("[179.5, 51.3]").unwrap();
+            assert_eq!(lnglat.lat(), 51.3);
+            assert_eq!(
+                serde_json::to_string(&LatLng::from(lnglat)).unwrap(),
+                "[51.3,179.5]"
+            );
+            assert!(serde_json::from_str::<LatLng>("[179.5, 51.3]").is_err());
+        }
     }
 }
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use super::NodeId;
+
+/// Ids of the nodes along a path, labelled with their cost from its origin.
+pub(crate) type LabelledPath = Vec<(NodeId, f64)>;
+
+/// Partial path of a multi-label search, reaching a node with the values
+/// tracked along it.
+pub(crate) struct Label<T> {
+    pub node: NodeId,
+    pub values: T,
+    pub parent: Option<usize>,
+    /// Whether the label is still on the front of its node.
+    pub alive: bool,
+}
+
+/// Labels of a multi-label search, with the front of each node: its labels
+/// not dominated by another one.
+pub(crate) struct Labels<T> {
+    labels: Vec<Label<T>>,
+    fronts: HashMap<NodeId, Vec<usize>>,
+    /// Checks if values are at least as good as others for the search.
+    dominates: fn(&T, &T) -> bool,
+}
+
+impl<T> Labels<T> {
+    /// Starts the labels with the one of the origin, at index 0.
+    pub fn new(origin: NodeId, values: T, dominates: fn(&T, &T) -> bool) -> Self {
+        Self {
+            labels: vec![Label {
+                node: origin,
+                values,
+                parent: None,
+                alive: true,
+            }],
+            fronts: HashMap::from([(origin, vec![0])]),
+            dominates,
+        }
+    }
+
+    pub fn get(&self, index: usize) -> &Label<T> {
+        &self.labels[index]
+    }
+
+    /// Checks if values reaching a node are dominated by its front.
+    pub fn dominated(&self, node: NodeId, values: &T) -> bool {
+        self.fronts.get(&node).is_some_and(|front| {
+            front
+                .iter()
+                .any(|&label| (self.dominates)(&self.labels[label].values, values))
+        })
+    }
+
+    /// Adds a label extending another one to the front of its node, removing
+    /// the labels it dominates from the front. Returns the index of the label.
+    pub fn insert(&mut self, node: NodeId, values: T, parent: usize) -> usize {
+        let index = self.labels.len();
+        let front = self.fronts.entry(node).or_default();
+        front.retain(|&label| {
+            let kept = !(self.dominates)(&values, &self.labels[label].values);
+            self.labels[label].alive &= kept;
+            kept
+        });
+        front.push(index);
+        self.labels.push(Label {
+            node,
+            values,
+            parent: Some(parent),
+            alive: true,
+        });
+        index
+    }
+
+    /// Removes the front of a node, returning the indices of its labels.
+    pub fn take_front(&mut self, node: NodeId) -> Vec<usize> {
+        self.fronts.remove(&node).unwrap_or_default()
+    }
+
+    /// Ids of the nodes along the path of a label, from the origin of the
+    /// search, labelled with the cost of their values.
+    pub fn path(&self, index: usize, cost: impl Fn(&T) -> f64) -> LabelledPath {
+        let mut path = Vec::new();
+        let mut step = Some(index);
+        while let Some(index) = step {
+            let label = &self.labels[index];
+            path.push((label.node, cost(&label.values)));
+            step = label.parent;
+        }
+        path.reverse();
+        path
+    }
+}
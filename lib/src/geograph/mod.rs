@@ -1,16 +1,67 @@
+pub mod area;
+pub mod budget;
+pub mod cache;
+pub mod calendar;
+pub mod cost;
+pub mod distance;
+pub mod edge;
 pub mod geoloc;
+mod label;
+pub mod overlay;
+pub mod pareto;
+pub mod range;
+pub mod route;
+pub mod speed;
+pub mod tariff;
+pub mod trajectory;
+pub mod travel_time;
+pub mod vessel;
+pub mod visitor;
 
 use std::cmp::{Ordering, Reverse};
-use std::collections::{BinaryHeap, HashMap};
-use std::iter;
-use std::sync::Arc;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime};
 
-pub use geoloc::{Coord, Distance, Geoloc, Geolocalizable, Lat, Lng, Path};
+pub use area::Polygon;
+pub use budget::{CancellationToken, Limit, SearchLimits};
+pub use cache::{CacheStats, RouteCache};
+pub use calendar::{Date, DateError, MonthDay, ValidityWindow};
+pub use cost::{EdgeCost, Haversine, Hops, TagPenalty, Weighted};
+pub use distance::Distance;
+pub use edge::EdgeAttributes;
+pub use geoloc::{Coord, Geoloc, GeolocError, Geolocalizable, Lat, LatLng, Lng, LngLat, Path};
+pub use overlay::Overlay;
+pub use pareto::ParetoRoute;
+pub use route::{Route, RouteOptions, Search};
+pub use speed::{Speed, SpeedProfile};
+pub use tariff::Tariff;
+pub use trajectory::{Sampling, TrajectoryPoint};
+pub use travel_time::{TravelTimeError, TravelTimeFunction};
+pub use vessel::{EdgeLimits, SizeClass, Vessel};
+pub use visitor::{
+    FrontierEvent, FrontierEventKind, FrontierRecorder, SearchVisitor, SettledCounter,
+};
 
-#[derive(Debug, PartialEq)]
+use area::Corridor;
+use budget::SearchResult;
+use calendar::{from_epoch_seconds, SECONDS_PER_HOUR};
+use cost::TotalCost;
+use route::Query;
+use speed::edge_length;
+
+/// Growth of the detour factor of a corridor every time no route is found
+/// within it, and number of retries before searching without any corridor.
+const CORRIDOR_GROWTH: f64 = 2.0;
+const CORRIDOR_RETRIES: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PathType {
     Direct,
     ViaWaypoints,
+    /// The search was aborted on reaching one of its limits,
+    /// and the path goes directly to the destination.
+    Aborted(Limit),
 }
 
 /// Represents a unique identifier for a node.
@@ -25,6 +76,9 @@ pub struct Node {
     pub id: NodeId,
     pub waypoints: Arc<[NodeId]>,
     geoloc: Geoloc,
+    /// Whether the edges from the node may have attributes in its geograph,
+    /// to avoid looking them up on every edge of a search.
+    has_edges: bool,
 }
 
 impl Node {
@@ -33,6 +87,7 @@ impl Node {
             id,
             geoloc,
             waypoints: waypoints.into_iter().collect(),
+            has_edges: false,
         }
     }
 }
@@ -47,26 +102,74 @@ impl Geolocalizable for Node {
 pub struct Geograph {
     pub name: String,
     graph: HashMap<NodeId, Node>,
+    edges: HashMap<(NodeId, NodeId), EdgeAttributes>,
+    /// Nodes with edges in `edges`, to flag the nodes added after them.
+    edge_sources: HashSet<NodeId>,
+    /// Whether the geograph is undirected, computed on first use and reset
+    /// whenever its nodes or edges change.
+    undirected: OnceLock<bool>,
+    node_tags: HashMap<NodeId, Vec<String>>,
+    node_windows: HashMap<NodeId, Vec<ValidityWindow>>,
+    speed_profile: Option<SpeedProfile>,
 }
 
-pub type ShortestPath = (f64, Path<Geoloc>, PathType);
+pub type ShortestPath = (Distance, Path<Geoloc>, PathType);
+
+/// Distance and path type of a shortest path, without the path itself.
+pub type DistanceOnly = (Distance, PathType);
+
+/// Costs of the nodes reached by a search, and the nodes they are reached from.
+struct SearchTree {
+    costs: HashMap<NodeId, TotalCost>,
+    previous: HashMap<NodeId, NodeId>,
+}
+
+impl SearchTree {
+    /// Ids of the nodes along the path from the origin of the search to
+    /// a destination, labelled with their cost, if the destination is reached.
+    fn path(&self, origin: NodeId, destination: NodeId, start: f64) -> Option<Vec<(NodeId, f64)>> {
+        let mut path = Vec::new();
+        let mut step = destination;
+        while step != origin {
+            let &prev = self.previous.get(&step)?;
+            path.push((step, self.costs[&step].0));
+            step = prev;
+        }
+        path.push((origin, start));
+        path.reverse();
+        Some(path)
+    }
+}
 
 impl Geograph {
     pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
             graph: HashMap::new(),
+            edges: HashMap::new(),
+            edge_sources: HashSet::new(),
+            undirected: OnceLock::new(),
+            node_tags: HashMap::new(),
+            node_windows: HashMap::new(),
+            speed_profile: None,
         }
     }
 
-    /// Finds the closest node in the geograph to the given location.
+    /// Finds the closest node in the geograph to the given location,
+    /// among the nodes matching the predicate.
     /// Used to find the entry and exit points for the shortest path calculation.
-    fn closest(&self, loc: &impl Geolocalizable) -> Option<&Node> {
-        self.iter_nodes().min_by(|a, b| {
-            a.haversine(loc)
-                .partial_cmp(&b.haversine(loc))
-                .unwrap_or(Ordering::Equal)
-        })
+    pub(crate) fn closest_where(
+        &self,
+        loc: &impl Geolocalizable,
+        predicate: impl Fn(&Node) -> bool,
+    ) -> Option<&Node> {
+        self.iter_nodes()
+            .filter(|node| predicate(node))
+            .min_by(|a, b| {
+                a.haversine(loc)
+                    .partial_cmp(&b.haversine(loc))
+                    .unwrap_or(Ordering::Equal)
+            })
     }
 
     /// Calculates the distance between two geolocations within the geograph
@@ -80,46 +183,311 @@ impl Geograph {
     /// calculate the direct Haversine distance betweeen the locations.
     ///
     /// It returns a tuple with:
-    /// - Total distance traveled
+    /// - Total distance traveled, as a unit-aware `Distance`
     /// - List of geolocations along the path
     /// - PathType indicating if it was a direct path or went through nodes
     ///
+    /// To prune the search to a corridor between the origin and destination,
+    /// use `route` with `RouteOptions::within_corridor`.
     pub fn shortest_path(
         &self,
         origin: &impl Geolocalizable,
         destination: &impl Geolocalizable,
     ) -> ShortestPath {
-        let direct_path =
-            |o: Geoloc, d| (o.haversine(&d), Path::from(vec![o, d]), PathType::Direct);
+        self.route(origin, destination, &RouteOptions::default())
+            .into()
+    }
 
-        match (self.closest(origin), self.closest(destination)) {
+    /// Calculates the distance between two geolocations like `shortest_path`,
+    /// without building the path: the length of the edges is summed while
+    /// walking back the search from the destination. Suited to distance
+    /// matrices, where only the distances are needed.
+    pub fn distance_only(
+        &self,
+        origin: &impl Geolocalizable,
+        destination: &impl Geolocalizable,
+    ) -> DistanceOnly {
+        let options = RouteOptions::default();
+        let query = Query::new(self, &options);
+        let allowed = |node: &Node| query.allows_node(node);
+        let direct = (
+            Distance::from_km(origin.haversine(destination)),
+            PathType::Direct,
+        );
+        let (Some(entry), Some(exit)) = (
+            self.closest_where(origin, allowed),
+            self.closest_where(destination, allowed),
+        ) else {
+            return direct;
+        };
+        let Ok(tree) = self.search_tree(entry.id, &[exit.id], 0.0, &query) else {
+            return direct;
+        };
+
+        let mut distance = Distance::from_km(origin.haversine(entry) + exit.haversine(destination));
+        let mut step = exit;
+        while step.id != entry.id {
+            let Some(prev) = tree.previous.get(&step.id).and_then(|&id| self.get(id)) else {
+                return direct;
+            };
+            distance += edge_length(prev, step, self.edge_from(prev, step.id));
+            step = prev;
+        }
+        (distance, PathType::ViaWaypoints)
+    }
+
+    /// Calculates the distance between two geolocations like `shortest_path`,
+    /// aborting the search once it reaches any of the limits, with a path
+    /// type of `PathType::Aborted`.
+    pub fn shortest_path_within(
+        &self,
+        origin: &impl Geolocalizable,
+        destination: &impl Geolocalizable,
+        limits: &SearchLimits,
+    ) -> ShortestPath {
+        let options = RouteOptions::new().with_limits(limits.clone());
+        self.route(origin, destination, &options).into()
+    }
+
+    /// Calculates the route between two geolocations within the geograph,
+    /// minimizing the `EdgeCost` set in the options, which defaults to the
+    /// haversine distance.
+    ///
+    /// Like `shortest_path`, it finds the closest nodes to the origin and
+    /// destination and runs Dijkstra's algorithm between them, falling back
+    /// to a direct route if the destination is not reachable.
+    ///
+    /// Nodes and edges excluded by the options, such as those inside the
+    /// areas to avoid, are skipped both when finding the closest nodes and
+    /// during the search.
+    ///
+    /// The returned `Route` reports both the geometric length of the route
+    /// and its optimized cost, as well as its estimated duration when a speed
+    /// profile is set on the geograph or the options.
+    pub fn route(
+        &self,
+        origin: &impl Geolocalizable,
+        destination: &impl Geolocalizable,
+        options: &RouteOptions,
+    ) -> Route {
+        // Skip the snapping of searches cancelled or past their deadline
+        if let Some(limit) = options.limits.expired() {
+            return Route::aborted(origin.geoloc(), destination.geoloc(), limit);
+        }
+        let mut query = Query::new(self, options);
+        let allowed = |node: &Node| query.allows_node(node);
+
+        let mut route = match (
+            self.closest_where(origin, allowed),
+            self.closest_where(destination, allowed),
+        ) {
             (Some(origin_closest), Some(destination_closest)) => {
-                let shortest_path = self.dijsktra(origin_closest.id, destination_closest.id);
-
-                match shortest_path {
-                    Some(path) => {
-                        let path: Path<Geoloc> = iter::once(origin.geoloc())
-                            .chain(
-                                path.iter()
-                                    .filter_map(|id| self.get(*id).map(|node| node.geoloc())),
-                            )
-                            .chain(iter::once(destination.geoloc()))
-                            .collect::<Vec<_>>()
-                            .into();
-                        (path.length(), path, PathType::ViaWaypoints)
+                // Time-dependent searches start once the origin node is reached
+                let start = query.timed().map_or(0.0, |(_, profile)| {
+                    let access = Distance::from_km(origin.haversine(origin_closest));
+                    (access / profile.default_speed()).as_secs_f64()
+                });
+                let (entry, exit) = (origin_closest.id, destination_closest.id);
+                query.set_budget(origin_closest);
+                let range = (
+                    origin.haversine(origin_closest),
+                    destination.haversine(destination_closest),
+                );
+                let search = |query: &Query| {
+                    if query.range().is_some() {
+                        return self.range_search(entry, exit, start, range, query);
+                    }
+                    #[cfg(feature = "concurrency")]
+                    if query.delta_stepping() {
+                        return crate::concurrency::delta_stepping(self, entry, exit, start, query);
+                    }
+                    self.dijsktra(entry, exit, start, query)
+                };
+                // Searches within a corridor widen it until a route is found
+                let factors = options.corridor.into_iter().flat_map(|factor| {
+                    (0..=CORRIDOR_RETRIES).map(move |retry| factor * CORRIDOR_GROWTH.powi(retry))
+                });
+                let mut labels = Ok(None);
+                for factor in factors {
+                    let corridor = Corridor::new(origin_closest, destination_closest, factor);
+                    query.set_corridor(Some(corridor));
+                    labels = search(&query);
+                    if !matches!(labels, Ok(None)) {
+                        break;
                     }
-                    // If no path found, calculate the direct path between the origin and destination.
+                }
+                query.set_corridor(None);
+                if let Ok(None) = labels {
+                    labels = search(&query);
+                }
+                match labels {
+                    Ok(Some(labels)) => {
+                        let mut route = self.route_via(origin, destination, labels, start, &query);
+                        route.stations = self.refuelling_stops(&route.nodes, range, &query);
+                        route
+                    }
+                    // If no path found, calculate the direct route between the origin and destination.
                     // This can happen if the destination is not reachable from the origin.
-                    None => direct_path(origin.geoloc(), destination.geoloc()),
+                    Ok(None) => Route::direct(origin.geoloc(), destination.geoloc()),
+                    Err(limit) => Route::aborted(origin.geoloc(), destination.geoloc(), limit),
                 }
             }
-            // If any of the closest nodes is not found, calculate the direct path
+            // If any of the closest nodes is not found, calculate the direct route
             // between the origin and destination. Case for empty geographs.
-            _ => direct_path(origin.geoloc(), destination.geoloc()),
+            _ => Route::direct(origin.geoloc(), destination.geoloc()),
+        };
+        let departure = options.departure_time.filter(|_| query.timed().is_some());
+        route.duration = query
+            .speed_profile()
+            .map(|profile| self.route_duration(&route, profile, departure));
+        route.arrival = departure
+            .zip(route.duration)
+            .and_then(|(departure, duration)| departure.checked_add(duration));
+        route.price = options.tariff.map(|tariff| tariff.price(self, &route));
+        route
+    }
+
+    /// Calculates the route going through several stops in order, joining
+    /// the routes between consecutive stops. Its duration includes the dwell
+    /// time set in the options at every intermediate stop, and its price is
+    /// that of a single route, handled at its first and last nodes.
+    ///
+    /// Panics if there are less than two stops.
+    pub fn route_through<G: Geolocalizable>(&self, stops: &[G], options: &RouteOptions) -> Route {
+        assert!(stops.len() >= 2, "A route needs at least two stops");
+        let mut options = options.clone();
+        let mut legs = Vec::new();
+        for leg in stops.windows(2) {
+            let route = self.route(&leg[0], &leg[1], &options);
+            // Time-dependent legs depart once the previous one arrived and dwelled
+            options.departure_time = route
+                .arrival
+                .and_then(|arrival| arrival.checked_add(options.dwell));
+            legs.push(route);
         }
+        let mut route = Route::join(legs, options.dwell);
+        route.price = options.tariff.map(|tariff| tariff.price(self, &route));
+        route
+    }
+
+    /// Generates the timestamped trajectory of a route, departing at the given
+    /// time, sampled at regular intervals of time or distance. Its positions
+    /// follow the great circles between the points of the route, at the speed
+    /// of the profile for each of its edges.
+    pub fn trajectory(
+        &self,
+        route: &Route,
+        departure: SystemTime,
+        profile: &SpeedProfile,
+        sampling: Sampling,
+    ) -> Vec<TrajectoryPoint> {
+        let node = |index: usize| route.nodes.get(index).and_then(|&id| self.get(id));
+        let mut reached: Option<usize> = None;
+        let speeds = route.path.windows(2).map(|pair| {
+            let next = reached.map_or(0, |index| index + 1);
+            // Segments along an edge lead to its shape or its end node,
+            // others go to or from the origin, destination or via stops
+            let speed = match (reached.and_then(node), node(next)) {
+                (Some(from), Some(to)) => {
+                    let edge = self.edge_from(from, to.id);
+                    let geometry = edge.and_then(|edge| edge.geometry.as_deref());
+                    if pair[1] == to.geoloc() || geometry.is_some_and(|g| g.contains(&pair[1])) {
+                        profile.speed(edge)
+                    } else {
+                        profile.default_speed()
+                    }
+                }
+                _ => profile.default_speed(),
+            };
+            if node(next).is_some_and(|to| to.geoloc() == pair[1]) {
+                reached = Some(next);
+            }
+            speed
+        });
+        trajectory::sample(&route.path, speeds, departure, sampling)
     }
 
-    pub fn add(&mut self, node: Node) -> &mut Self {
+    /// Estimates the travel time of a route, at the default speed between
+    /// the geograph and the origin and destination. Time-dependent routes
+    /// last until the destination is reached from the time of their last node.
+    pub(crate) fn route_duration(
+        &self,
+        route: &Route,
+        profile: &SpeedProfile,
+        departure: Option<SystemTime>,
+    ) -> Duration {
+        let nodes: Vec<&Node> = route.nodes.iter().filter_map(|&id| self.get(id)).collect();
+        let (Some(first), Some(last)) = (nodes.first(), nodes.last()) else {
+            return route.distance / profile.default_speed();
+        };
+        let egress = Distance::from_km(last.haversine(&route.path[route.path.len() - 1]));
+        if let (Some(departure), Some(reached)) = (departure, route.timestamps.last()) {
+            return reached
+                .duration_since(departure)
+                .unwrap_or_default()
+                .saturating_add(egress / profile.default_speed());
+        }
+        let access = Distance::from_km(route.path[0].haversine(*first)) + egress;
+        nodes
+            .windows(2)
+            .map(|pair| profile.edge_duration(pair[0], pair[1], self.edge(pair[0].id, pair[1].id)))
+            .fold(access / profile.default_speed(), Duration::saturating_add)
+    }
+
+    /// Builds the route going from the origin to the destination through
+    /// the nodes found by the search, labelled with their cost from the
+    /// origin, including the shape of the edges between them.
+    pub(crate) fn route_via(
+        &self,
+        origin: &impl Geolocalizable,
+        destination: &impl Geolocalizable,
+        labels: Vec<(NodeId, f64)>,
+        start: f64,
+        query: &Query,
+    ) -> Route {
+        let nodes: Vec<NodeId> = labels.iter().map(|&(id, _)| id).collect();
+        let cost = labels.last().map_or(0.0, |&(_, cost)| cost) - start;
+        let (cost, timestamps) = match query.timed() {
+            // Time-dependent routes minimize the travel time, reported in hours
+            Some((departure, _)) => (
+                cost / SECONDS_PER_HOUR,
+                labels
+                    .iter()
+                    .map(|&(_, elapsed)| from_epoch_seconds(departure + elapsed))
+                    .collect(),
+            ),
+            None => (cost, Vec::new()),
+        };
+
+        let mut path = vec![origin.geoloc()];
+        for (i, &id) in nodes.iter().enumerate() {
+            let geometry = i
+                .checked_sub(1)
+                .and_then(|prev| self.edge(nodes[prev], id))
+                .and_then(|edge| edge.geometry.as_ref());
+            path.extend(geometry.into_iter().flatten());
+            path.extend(self.get(id).map(|node| node.geoloc()));
+        }
+        path.push(destination.geoloc());
+
+        let path = Path::from(path);
+        Route {
+            distance: path.length(),
+            cost,
+            path,
+            path_type: PathType::ViaWaypoints,
+            nodes,
+            duration: None,
+            arrival: None,
+            timestamps,
+            price: None,
+            stations: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, mut node: Node) -> &mut Self {
+        node.has_edges = self.edge_sources.contains(&node.id);
+        self.undirected.take();
         self.graph.insert(node.id, node);
         self
     }
@@ -132,6 +500,168 @@ impl Geograph {
         self.graph.get(&id).cloned()
     }
 
+    /// Sets the attributes of the edge going from a node to one of its waypoints.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, attributes: EdgeAttributes) -> &mut Self {
+        *self.edge_entry(from, to) = attributes;
+        self
+    }
+
+    /// Gets the attributes of an edge to set them, flagging its node.
+    fn edge_entry(&mut self, from: NodeId, to: NodeId) -> &mut EdgeAttributes {
+        self.undirected.take();
+        if self.edge_sources.insert(from) {
+            if let Some(node) = self.graph.get_mut(&from) {
+                node.has_edges = true;
+            }
+        }
+        self.edges.entry((from, to)).or_default()
+    }
+
+    /// Gets the attributes of the edge going from a node to one of its waypoints,
+    /// if any were set.
+    pub fn edge(&self, from: NodeId, to: NodeId) -> Option<&EdgeAttributes> {
+        // Avoid hashing on geographs without edge attributes, the most common case
+        if self.edges.is_empty() {
+            return None;
+        }
+        self.edges.get(&(from, to))
+    }
+
+    /// Gets the attributes of the edge going from a node of the geograph to
+    /// one of its waypoints, without a lookup if the node has none.
+    pub(crate) fn edge_from(&self, from: &Node, to: NodeId) -> Option<&EdgeAttributes> {
+        if !from.has_edges {
+            return None;
+        }
+        self.edges.get(&(from.id, to))
+    }
+
+    pub fn iter_edges(&self) -> impl Iterator<Item = ((NodeId, NodeId), &EdgeAttributes)> {
+        self.edges
+            .iter()
+            .map(|(&key, attributes)| (key, attributes))
+    }
+
+    /// Adds a tag to the edge going from a node to one of its waypoints,
+    /// setting its attributes if it had none.
+    pub fn tag_edge(&mut self, from: NodeId, to: NodeId, tag: &str) -> &mut Self {
+        let attributes = self.edge_entry(from, to);
+        if !attributes.has_tag(tag) {
+            attributes.tags.push(tag.to_string());
+        }
+        self
+    }
+
+    /// Sets the limits of the edge going from a node to one of its waypoints,
+    /// setting its attributes if it had none.
+    pub fn limit_edge(&mut self, from: NodeId, to: NodeId, limits: EdgeLimits) -> &mut Self {
+        self.edge_entry(from, to).limits = Some(limits);
+        self
+    }
+
+    /// Adds a validity window to the edge going from a node to one of its
+    /// waypoints, setting its attributes if it had none. Edges with windows
+    /// are closed outside of them for the routes with a departure date.
+    pub fn add_edge_window(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        window: ValidityWindow,
+    ) -> &mut Self {
+        self.edge_entry(from, to).validity.push(window);
+        self
+    }
+
+    /// Sets the travel time function of the edge going from a node to one of
+    /// its waypoints, setting its attributes if it had none.
+    pub fn set_travel_time(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        function: TravelTimeFunction,
+    ) -> &mut Self {
+        self.edge_entry(from, to).travel_time = Some(function);
+        self
+    }
+
+    /// Sets the speeds used to estimate the duration of the routes,
+    /// unless the route options set their own.
+    pub fn set_speed_profile(&mut self, profile: SpeedProfile) -> &mut Self {
+        self.speed_profile = Some(profile);
+        self
+    }
+
+    pub fn speed_profile(&self) -> Option<&SpeedProfile> {
+        self.speed_profile.as_ref()
+    }
+
+    /// Adds a tag to the node, such as `port` or `charging_station`.
+    pub fn tag_node(&mut self, id: NodeId, tag: &str) -> &mut Self {
+        let tags = self.node_tags.entry(id).or_default();
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+        self
+    }
+
+    /// Adds a validity window to the node. Nodes with windows are closed
+    /// outside of them for the routes with a departure date.
+    pub fn add_node_window(&mut self, id: NodeId, window: ValidityWindow) -> &mut Self {
+        self.node_windows.entry(id).or_default().push(window);
+        self
+    }
+
+    pub fn node_windows(&self, id: NodeId) -> &[ValidityWindow] {
+        self.node_windows.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn node_tags(&self, id: NodeId) -> &[String] {
+        self.node_tags.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn has_node_tag(&self, id: NodeId, tag: &str) -> bool {
+        self.node_tags(id).iter().any(|t| t == tag)
+    }
+
+    /// Iterates over the ids of the nodes with the given tag.
+    pub fn iter_tagged_nodes<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = NodeId> + 'a {
+        self.node_tags
+            .iter()
+            .filter(move |(_, tags)| tags.iter().any(|t| t == tag))
+            .map(|(&id, _)| id)
+    }
+
+    /// Checks if every node is a waypoint of its own waypoints, and if the
+    /// attributes of every edge mirror those of the edge going back, with
+    /// the same geometry reversed, so that the paths of the geograph can be
+    /// travelled both ways.
+    ///
+    /// The check goes over every edge, so its result is kept until the
+    /// geograph is changed.
+    pub fn is_undirected(&self) -> bool {
+        *self.undirected.get_or_init(|| self.check_undirected())
+    }
+
+    fn check_undirected(&self) -> bool {
+        let mutual = self.iter_nodes().all(|node| {
+            node.waypoints.iter().all(|&id| {
+                self.get(id)
+                    .is_some_and(|waypoint| waypoint.waypoints.contains(&node.id))
+            })
+        });
+        mutual
+            && self.iter_edges().all(|((from, to), attributes)| {
+                let mut mirrored = attributes.clone();
+                if let Some(geometry) = &mut mirrored.geometry {
+                    geometry.reverse();
+                }
+                self.edge(to, from)
+                    .map_or(mirrored == EdgeAttributes::default(), |back| {
+                        *back == mirrored
+                    })
+            })
+    }
+
     pub fn iter_nodes(&self) -> impl Iterator<Item = &Node> {
         self.graph.values()
     }
@@ -145,63 +675,108 @@ impl Geograph {
         self.len() == 0
     }
 
-    /// Determines the shortest path between two nodes in the geograph
-    /// using Dijsktra's algorithm and the Haversine formula.
-    fn dijsktra(&self, origin: NodeId, destination: NodeId) -> Option<Vec<NodeId>> {
+    /// Determines the cheapest path between two nodes in the geograph
+    /// using Dijsktra's algorithm and the `EdgeCost` set in the query options.
+    /// Returns the ids of the nodes along the path and its total cost.
+    ///
+    /// The search starts with a cost of `start` at the origin, and returns
+    /// the nodes of the path labelled with their cost. As time-dependent
+    /// travel times are FIFO, the same search finds the earliest arrival,
+    /// with the time elapsed since the departure as cost.
+    fn dijsktra(
+        &self,
+        origin: NodeId,
+        destination: NodeId,
+        start: f64,
+        query: &Query,
+    ) -> SearchResult {
+        let tree = self.search_tree(origin, &[destination], start, query)?;
+        let path = tree.path(origin, destination, start);
+        if let (Some(visitor), Some(path)) = (query.visitor(), &path) {
+            visitor.found(path);
+        }
+        Ok(path)
+    }
+
+    /// Determines the cheapest paths from a node to several others with a
+    /// single run of Dijkstra's algorithm, labelling the nodes along each path
+    /// with their cost, or `None` for the destinations not reachable.
+    #[cfg(feature = "concurrency")]
+    pub(crate) fn dijkstra_to_many(
+        &self,
+        origin: NodeId,
+        destinations: &[NodeId],
+        query: &Query,
+    ) -> Vec<Option<Vec<(NodeId, f64)>>> {
+        match self.search_tree(origin, destinations, 0.0, query) {
+            Ok(tree) => destinations
+                .iter()
+                .map(|&destination| tree.path(origin, destination, 0.0))
+                .collect(),
+            Err(_) => vec![None; destinations.len()],
+        }
+    }
+
+    /// Runs Dijkstra's algorithm until all the destinations are settled, or
+    /// no other node is reachable, returning the costs of the nodes reached
+    /// and the node each is reached from.
+    fn search_tree(
+        &self,
+        origin: NodeId,
+        destinations: &[NodeId],
+        start: f64,
+        query: &Query,
+    ) -> Result<SearchTree, Limit> {
         let mut queue = BinaryHeap::new();
-        let mut distances: HashMap<NodeId, Distance> = HashMap::new();
+        let mut costs: HashMap<NodeId, TotalCost> = HashMap::new();
         let mut previous: HashMap<NodeId, NodeId> = HashMap::new();
+        let mut remaining: HashSet<NodeId> = destinations.iter().copied().collect();
 
-        // Initialize distances and queue
-        self.graph.keys().for_each(|&node_id| {
-            let distance = if node_id == origin {
-                Distance(0.0)
-            } else {
-                Distance(f64::INFINITY)
-            };
-            distances.insert(node_id, distance);
-            queue.push(Reverse((distance, node_id)));
-        });
+        // Initialize the queue with the origin, other nodes are queued once reached
+        costs.insert(origin, TotalCost(start));
+        queue.push(Reverse((TotalCost(start), origin)));
 
-        while let Some(Reverse((Distance(dist), current))) = queue.pop() {
-            // Early exit if the destination node is reached
-            if current == destination {
-                let mut path = Vec::new();
-                let mut step = destination;
-                while step != origin {
-                    if let Some(&prev) = previous.get(&step) {
-                        path.push(step);
-                        step = prev;
-                    } else {
-                        return None; // No path found
-                    }
-                }
-                path.push(origin);
-                path.reverse();
-                return Some(path);
+        while let Some(Reverse((cost, current))) = queue.pop() {
+            // Skip outdated queue entries for nodes already reached at a lower cost
+            if costs.get(&current).is_some_and(|&best| cost > best) {
+                continue;
+            }
+            if let Some(node) = self.graph.get(&current) {
+                query.settle(node, cost.0)?;
+            }
+
+            // Early exit once all the destinations are reached
+            if remaining.remove(&current) && remaining.is_empty() {
+                break;
             }
 
             // Process each neighbor
             if let Some(node) = self.graph.get(&current) {
                 for &neighbor_id in node.waypoints.iter() {
                     let neighbor = self.get(neighbor_id).expect("Missing neighbor");
-                    let additional_distance = node.haversine(neighbor);
-                    let total_distance = Distance(dist + additional_distance);
+                    let edge = self.edge_from(node, neighbor_id);
+                    let Some(additional_cost) = query.edge_cost(node, neighbor, edge, cost.0)
+                    else {
+                        continue; // Impassable or excluded edge
+                    };
+                    let total_cost = TotalCost(cost.0 + additional_cost);
 
-                    let neighbor_distance = *distances
-                        .get(&neighbor_id)
-                        .unwrap_or(&Distance(f64::INFINITY));
+                    let neighbor_cost =
+                        *costs.get(&neighbor_id).unwrap_or(&TotalCost(f64::INFINITY));
 
-                    if total_distance < neighbor_distance {
-                        distances.insert(neighbor_id, total_distance);
+                    if total_cost < neighbor_cost {
+                        if let Some(visitor) = query.visitor() {
+                            visitor.relax(node, neighbor, total_cost.0);
+                        }
+                        costs.insert(neighbor_id, total_cost);
                         previous.insert(neighbor_id, current);
-                        queue.push(Reverse((total_distance, neighbor_id)));
+                        queue.push(Reverse((total_cost, neighbor_id)));
                     }
                 }
             }
         }
 
-        None // If no path is found
+        Ok(SearchTree { costs, previous })
     }
 }
 
@@ -222,12 +797,57 @@ macro_rules! build_geograph_mod {
         ) -> ShortestPath {
             GEOGRAPH.shortest_path(origin, destination)
         }
+
+        pub fn shortest_path_cached(
+            origin: &impl Geolocalizable,
+            destination: &impl Geolocalizable,
+            cache: &RouteCache,
+        ) -> ShortestPath {
+            GEOGRAPH.shortest_path_cached(origin, destination, cache)
+        }
+
+        pub fn distance_only(
+            origin: &impl Geolocalizable,
+            destination: &impl Geolocalizable,
+        ) -> DistanceOnly {
+            GEOGRAPH.distance_only(origin, destination)
+        }
+
+        pub fn shortest_path_within(
+            origin: &impl Geolocalizable,
+            destination: &impl Geolocalizable,
+            limits: &SearchLimits,
+        ) -> ShortestPath {
+            GEOGRAPH.shortest_path_within(origin, destination, limits)
+        }
+
+        pub fn route(
+            origin: &impl Geolocalizable,
+            destination: &impl Geolocalizable,
+            options: &RouteOptions,
+        ) -> Route {
+            GEOGRAPH.route(origin, destination, options)
+        }
+
+        pub fn route_through<G: Geolocalizable>(stops: &[G], options: &RouteOptions) -> Route {
+            GEOGRAPH.route_through(stops, options)
+        }
+
+        pub fn pareto_routes(
+            origin: &impl Geolocalizable,
+            destination: &impl Geolocalizable,
+            criteria: &[&(dyn EdgeCost + Sync)],
+            options: &RouteOptions,
+        ) -> Vec<ParetoRoute> {
+            GEOGRAPH.pareto_routes(origin, destination, criteria, options)
+        }
     };
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::UNIX_EPOCH;
 
     fn geograph_fixture() -> Geograph {
         let mut geograph = Geograph::new("Test Geograph");
@@ -268,6 +888,59 @@ mod tests {
         assert!(non_existent_node.is_none());
     }
 
+    #[test]
+    fn test_edges() {
+        let mut geograph = geograph_fixture();
+        let ferry = EdgeAttributes {
+            weight: Some(10.0),
+            tags: vec!["ferry".to_string()],
+            ..Default::default()
+        };
+        geograph.add_edge(1, 2, ferry.clone());
+
+        assert_eq!(geograph.edge(1, 2), Some(&ferry));
+        assert!(geograph.edge(2, 1).is_none());
+        assert!(geograph.edge(1, 2).unwrap().has_tag("ferry"));
+        assert_eq!(geograph.iter_edges().count(), 1);
+    }
+
+    #[test]
+    fn test_edges_from_nodes() {
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (0.0, 0.0), vec![1]))
+            .tag_edge(0, 1, "ferry")
+            .tag_edge(1, 0, "ferry")
+            .add(Node::new(1, (0.0, 1.0), vec![0, 2]))
+            .add(Node::new(2, (0.0, 2.0), vec![1]));
+
+        // Nodes added before and after their edges both find them
+        let edge = |from, to| geograph.edge_from(geograph.get(from).unwrap(), to);
+        assert!(edge(0, 1).unwrap().has_tag("ferry"));
+        assert!(edge(1, 0).unwrap().has_tag("ferry"));
+        assert!(edge(1, 2).is_none());
+        assert!(edge(2, 1).is_none());
+    }
+
+    #[test]
+    fn test_tags() {
+        let mut geograph = geograph_fixture();
+        geograph
+            .tag_node(1, "port")
+            .tag_node(1, "port")
+            .tag_node(3, "port")
+            .tag_edge(1, 2, "canal");
+
+        assert_eq!(geograph.node_tags(1), ["port"]);
+        assert!(geograph.node_tags(2).is_empty());
+        assert!(geograph.has_node_tag(3, "port"));
+        assert!(geograph.edge(1, 2).unwrap().has_tag("canal"));
+
+        let mut ports: Vec<_> = geograph.iter_tagged_nodes("port").collect();
+        ports.sort();
+        assert_eq!(ports, vec![1, 3]);
+    }
+
     #[test]
     fn test_nodes() {
         let geograph = geograph_fixture();
@@ -282,7 +955,9 @@ mod tests {
         let origin = geograph.get(0).unwrap();
         let very_close_to_origin = Node::new(6, (0.01, 0.01), vec![1]);
 
-        let closest = geograph.closest(&very_close_to_origin).unwrap();
+        let closest = geograph
+            .closest_where(&very_close_to_origin, |_| true)
+            .unwrap();
 
         assert_eq!(closest.id, origin.id);
     }
@@ -290,17 +965,459 @@ mod tests {
     #[test]
     fn test_shortest_path() {
         let geograph = geograph_fixture();
+        let options = RouteOptions::default();
+        let dijsktra = |origin, destination| {
+            geograph
+                .dijsktra(origin, destination, 0.0, &Query::new(&geograph, &options))
+                .unwrap()
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(dijsktra(0, 5), vec![0, 1, 2, 5]);
+        assert_eq!(dijsktra(2, 0), vec![2, 1, 0]);
+        assert_eq!(dijsktra(4, 0), vec![4, 1, 0]);
+        assert_eq!(dijsktra(5, 0), vec![5, 4, 1, 0]);
+    }
+
+    #[test]
+    fn test_distance_only() {
+        let mut geograph = geograph_fixture();
+        geograph.add_edge(
+            1,
+            2,
+            EdgeAttributes {
+                geometry: Some(vec![(1.0, 2.0)]),
+                ..Default::default()
+            },
+        );
+        let (from, to) = ((0.1, 0.0), (5.0, 5.2));
+
+        let (distance, path_type) = geograph.distance_only(&from, &to);
+        let (expected, _, _) = geograph.shortest_path(&from, &to);
+        assert!((distance.km() - expected.km()).abs() < 1e-9);
+        assert_eq!(path_type, PathType::ViaWaypoints);
+
+        geograph.add(Node::new(6, (-5.0, -5.0), vec![]));
+        let (distance, path_type) = geograph.distance_only(&from, &(-5.0, -5.0));
+        assert_eq!(distance.km(), from.haversine(&(-5.0, -5.0)));
+        assert_eq!(path_type, PathType::Direct);
+    }
+
+    #[test]
+    fn test_route_with_edge_cost() {
+        let mut geograph = geograph_fixture();
+        geograph.add_edge(
+            1,
+            4,
+            EdgeAttributes {
+                weight: Some(0.0),
+                geometry: Some(vec![(2.0, 3.0)]),
+                ..Default::default()
+            },
+        );
+        let (from, to) = ((0.0, 0.0), (5.0, 5.0));
+
+        let shortest = geograph.route(&from, &to, &RouteOptions::default());
+        assert_eq!(shortest.nodes, vec![0, 1, 2, 5]);
+        assert_eq!(shortest.distance.km(), shortest.cost);
+
+        let cheapest = geograph.route(&from, &to, &RouteOptions::new().with_cost(&Weighted));
+        assert_eq!(cheapest.nodes, vec![0, 1, 4, 5]);
+        assert_eq!(cheapest.path.len(), 7);
+        assert_eq!(cheapest.path[3], (2.0, 3.0));
+        assert!(cheapest.cost < shortest.cost);
+        assert!(cheapest.distance > shortest.distance);
+        assert_eq!(cheapest.distance, cheapest.path.length());
+    }
+
+    #[test]
+    fn test_route_with_impassable_edges() {
+        let geograph = geograph_fixture();
+        let avoid_2 = |from: &Node, to: &Node, edge: Option<&EdgeAttributes>| {
+            (to.id != 2)
+                .then(|| Haversine.cost(from, to, edge))
+                .flatten()
+        };
+        let route = geograph.route(
+            &(0.0, 0.0),
+            &(5.0, 5.0),
+            &RouteOptions::new().with_cost(&avoid_2),
+        );
+        assert_eq!(route.path_type, PathType::ViaWaypoints);
+        assert!(!route.nodes.contains(&2));
+
+        let nowhere = |_: &Node, _: &Node, _: Option<&EdgeAttributes>| None;
+        let route = geograph.route(
+            &(0.0, 0.0),
+            &(5.0, 5.0),
+            &RouteOptions::new().with_cost(&nowhere),
+        );
+        assert_eq!(route.path_type, PathType::Direct);
+        assert_eq!(route.cost, 0.0);
+    }
+
+    #[test]
+    fn test_route_avoiding_areas() {
+        let mut geograph = Geograph::new("Detour Geograph");
+        geograph
+            .add(Node::new(0, (0.0, 0.0), vec![1, 3]))
+            .add(Node::new(1, (0.0, 1.0), vec![0, 2]))
+            .add(Node::new(2, (0.0, 2.0), vec![1, 3]))
+            .add(Node::new(3, (1.0, 1.0), vec![0, 2]));
+        let around = |lat: f64, lng: f64| {
+            let ring = [(-0.1, -0.1), (0.1, -0.1), (0.1, 0.1), (-0.1, 0.1)]
+                .iter()
+                .map(|(dlng, dlat)| LngLat::new(lng + dlng, lat + dlat).unwrap())
+                .collect();
+            Polygon::new(vec![ring])
+        };
+        let route = |options: &RouteOptions| geograph.route(&(0.0, 0.0), &(0.0, 2.0), options);
+
+        assert_eq!(route(&RouteOptions::default()).nodes, vec![0, 1, 2]);
+        assert_eq!(
+            route(&RouteOptions::new().avoid(around(0.0, 1.0))).nodes,
+            vec![0, 3, 2]
+        );
+
+        // Edges crossing the area are skipped too, even if their nodes are outside
+        assert_eq!(
+            route(&RouteOptions::new().avoid(around(0.0, 1.5))).nodes,
+            vec![0, 3, 2]
+        );
+
+        // Excluded nodes are not used as entry points
+        assert_eq!(
+            route(&RouteOptions::new().avoid(around(0.0, 2.0))).nodes,
+            vec![0, 1]
+        );
+    }
+
+    #[test]
+    fn test_route_with_overlay() {
+        let geograph = geograph_fixture();
+        let mut overlay = Overlay::new();
+        let route = |overlay: &Overlay| {
+            geograph
+                .route(
+                    &(0.0, 0.0),
+                    &(5.0, 5.0),
+                    &RouteOptions::new().with_overlay(overlay),
+                )
+                .nodes
+        };
 
-        let path = geograph.dijsktra(0, 5).unwrap();
-        assert_eq!(path, vec![0, 1, 2, 5]);
+        overlay.close_node(2);
+        assert!(!route(&overlay).contains(&2));
 
-        let path = geograph.dijsktra(2, 0).unwrap();
-        assert_eq!(path, vec![2, 1, 0]);
+        overlay.reset().close_edge(2, 5).penalize_node(4, 2.0);
+        assert_eq!(route(&overlay), vec![0, 1, 3, 5]);
 
-        let path = geograph.dijsktra(4, 0).unwrap();
-        assert_eq!(path, vec![4, 1, 0]);
+        overlay.close_node(0);
+        assert_eq!(route(&overlay).first(), Some(&1));
+
+        overlay.reset();
+        assert_eq!(route(&overlay), vec![0, 1, 2, 5]);
+    }
+
+    #[test]
+    fn test_route_avoiding_tags() {
+        let mut geograph = geograph_fixture();
+        geograph.tag_node(2, "closed").tag_edge(3, 5, "closed");
+        let options = RouteOptions::new().avoid_tag("closed");
+
+        let route = geograph.route(&(0.0, 0.0), &(5.0, 5.0), &options);
+        assert_eq!(route.nodes, vec![0, 1, 4, 5]);
+    }
+
+    #[test]
+    fn test_route_for_vessel() {
+        let mut geograph = geograph_fixture();
+        let shallow = EdgeAttributes {
+            limits: Some(EdgeLimits {
+                max_draft: Some(10.0),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        geograph.add_edge(1, 2, shallow.clone());
+        geograph.add_edge(2, 5, shallow);
+        let route = |vessel: Vessel| {
+            geograph
+                .route(
+                    &(0.0, 0.0),
+                    &(5.0, 5.0),
+                    &RouteOptions::new().for_vessel(vessel),
+                )
+                .nodes
+        };
+
+        let feeder = Vessel {
+            draft: Some(8.0),
+            ..Default::default()
+        };
+        let tanker = Vessel {
+            draft: Some(20.0),
+            ..Default::default()
+        };
+        assert_eq!(route(feeder), vec![0, 1, 2, 5]);
+        assert!(!route(tanker).contains(&2));
+    }
+
+    #[test]
+    fn test_route_on_date() {
+        let mut geograph = geograph_fixture();
+        let summer =
+            ValidityWindow::yearly(MonthDay::new(6, 1).unwrap(), MonthDay::new(9, 30).unwrap());
+        let route = |geograph: &Geograph, date: &str| {
+            let options = RouteOptions::new().departing_on(date.parse().unwrap());
+            geograph.route(&(0.0, 0.0), &(5.0, 5.0), &options).nodes
+        };
+
+        geograph.add_edge_window(1, 2, summer);
+        assert_eq!(route(&geograph, "2024-07-15"), vec![0, 1, 2, 5]);
+        assert!(!route(&geograph, "2024-12-15")
+            .windows(2)
+            .any(|e| e == [1, 2]));
+        assert_eq!(
+            geograph
+                .route(&(0.0, 0.0), &(5.0, 5.0), &RouteOptions::new())
+                .nodes,
+            vec![0, 1, 2, 5]
+        );
+
+        geograph.add_node_window(2, summer);
+        assert!(!route(&geograph, "2024-12-15").contains(&2));
+    }
+
+    #[test]
+    fn test_route_duration() {
+        let mut geograph = geograph_fixture();
+        let route = geograph.route(&(0.0, 0.0), &(5.0, 5.0), &RouteOptions::new());
+        assert_eq!(route.duration, None);
+
+        geograph.set_speed_profile(SpeedProfile::new(Speed::from_kmh(100.0)));
+        let route = geograph.route(&(0.0, 0.0), &(5.0, 5.0), &RouteOptions::new());
+        let duration = route.duration.unwrap().as_secs_f64();
+        assert!((duration - route.distance.km() * 36.0).abs() < 1e-6);
+
+        let slow = SpeedProfile::new(Speed::from_kmh(50.0));
+        let options = RouteOptions::new().with_speed_profile(&slow);
+        let route = geograph.route(&(0.0, 0.0), &(5.0, 5.0), &options);
+        assert!((route.duration.unwrap().as_secs_f64() - duration * 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fastest_route() {
+        let mut geograph = geograph_fixture();
+        geograph.tag_edge(1, 3, "highway").tag_edge(3, 5, "highway");
+        geograph.set_speed_profile(
+            SpeedProfile::new(Speed::from_kmh(50.0)).with("highway", Speed::from_kmh(120.0)),
+        );
+
+        let shortest = geograph.route(&(0.0, 0.0), &(5.0, 5.0), &RouteOptions::new());
+        let fastest = geograph.route(&(0.0, 0.0), &(5.0, 5.0), &RouteOptions::new().fastest());
+
+        assert_eq!(shortest.nodes, vec![0, 1, 2, 5]);
+        assert_eq!(fastest.nodes, vec![0, 1, 3, 5]);
+        assert!(fastest.duration < shortest.duration);
+        assert!(
+            (fastest.duration.unwrap().as_secs_f64() - fastest.cost * SECONDS_PER_HOUR).abs()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn test_route_within_corridor() {
+        // A line along the equator, with a detour north through node 3
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (0.0, 0.0), vec![1, 3]))
+            .add(Node::new(1, (0.0, 1.0), vec![0, 2]))
+            .add(Node::new(2, (0.0, 2.0), vec![1, 3]))
+            .add(Node::new(3, (3.0, 1.0), vec![0, 2]));
+        let (from, to) = ((0.0, 0.0), (0.0, 2.0));
+        let options = RouteOptions::new().within_corridor(1.2);
+
+        let route = geograph.route(&from, &to, &options);
+        assert_eq!(route.nodes, vec![0, 1, 2]);
+
+        // The detour is far outside of the corridor, which is widened until it's found
+        let mut overlay = Overlay::new();
+        overlay.close_node(1);
+        let route = geograph.route(&from, &to, &options.clone().with_overlay(&overlay));
+        assert_eq!(route.nodes, vec![0, 3, 2]);
+
+        // Cheaper routes outside of the corridor are missed
+        let weight = EdgeAttributes {
+            weight: Some(1.0),
+            ..Default::default()
+        };
+        geograph
+            .add_edge(0, 3, weight.clone())
+            .add_edge(3, 2, weight);
+        let cheaper = RouteOptions::new().with_cost(&Weighted);
+        assert_eq!(geograph.route(&from, &to, &cheaper).nodes, vec![0, 3, 2]);
+        let route = geograph.route(&from, &to, &cheaper.within_corridor(1.2));
+        assert_eq!(route.nodes, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_cheapest_route() {
+        let mut geograph = geograph_fixture();
+        geograph
+            .tag_edge(1, 2, "toll")
+            .tag_edge(2, 5, "toll")
+            .tag_node(2, "toll")
+            .tag_node(0, "port")
+            .tag_node(5, "port");
+        let tariff = Tariff::new(100.0, 1.0)
+            .with_passage_fee("toll", 500.0)
+            .with_tag_handling("port", 20.0);
+
+        let shortest = geograph.route(
+            &(0.0, 0.0),
+            &(5.0, 5.0),
+            &RouteOptions::new().with_tariff(&tariff),
+        );
+        let cheapest = geograph.route(
+            &(0.0, 0.0),
+            &(5.0, 5.0),
+            &RouteOptions::new().with_tariff(&tariff).cheapest(),
+        );
+
+        assert_eq!(shortest.nodes, vec![0, 1, 2, 5]);
+        assert_eq!(
+            shortest.price,
+            Some(100.0 + shortest.distance.km() + 500.0 + 40.0)
+        );
+        assert!(!cheapest.nodes.contains(&2));
+        assert_eq!(cheapest.price, Some(100.0 + cheapest.distance.km() + 40.0));
+        assert!(cheapest.price < shortest.price);
+        assert_eq!(
+            geograph
+                .route(&(0.0, 0.0), &(5.0, 5.0), &RouteOptions::new())
+                .price,
+            None
+        );
+    }
+
+    #[test]
+    fn test_route_through_stops() {
+        let mut geograph = geograph_fixture();
+        geograph.set_speed_profile(SpeedProfile::new(Speed::from_kmh(100.0)));
+        let dwell = Duration::from_secs(3_600);
+        let options = RouteOptions::new().with_dwell(dwell);
+        let stops = [(0.0, 0.0), (3.0, 3.0), (5.0, 5.0)];
+
+        let route = geograph.route_through(&stops, &options);
+        let first = geograph.route(&stops[0], &stops[1], &options);
+        let second = geograph.route(&stops[1], &stops[2], &options);
+
+        assert_eq!(route.nodes, vec![0, 1, 3, 5]);
+        assert_eq!(route.path.len(), first.path.len() + second.path.len() - 1);
+        assert_eq!(route.distance, first.distance + second.distance);
+        assert_eq!(
+            route.duration,
+            Some(first.duration.unwrap() + dwell + second.duration.unwrap())
+        );
+    }
+
+    #[test]
+    fn test_time_dependent_route() {
+        let mut geograph = geograph_fixture();
+        geograph.set_speed_profile(SpeedProfile::new(Speed::from_kmh(100.0)));
+        let hours = |hours: u64| Duration::from_secs(hours * 3_600);
+        // Congested from 05:00 to 11:00, peaking at 08:00
+        let rush_hours = TravelTimeFunction::daily(&[
+            (hours(5), hours(1)),
+            (hours(8), hours(4)),
+            (hours(11), hours(1)),
+        ])
+        .unwrap();
+        geograph.set_travel_time(1, 2, rush_hours);
+
+        let departing_at = |time: u64| {
+            let options = RouteOptions::new().departing_at(UNIX_EPOCH + hours(time));
+            geograph.route(&(0.0, 0.0), &(5.0, 5.0), &options)
+        };
+        let night = departing_at(2);
+        let rush = departing_at(6);
+
+        assert_eq!(night.nodes, vec![0, 1, 2, 5]);
+        assert!(!rush.nodes.contains(&2));
+        assert_eq!(rush.timestamps.len(), rush.nodes.len());
+        assert_eq!(rush.timestamps[0], UNIX_EPOCH + hours(6));
+        assert!(rush.timestamps.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(
+            rush.arrival,
+            Some(UNIX_EPOCH + hours(6) + rush.duration.unwrap())
+        );
+
+        let second = night.timestamps[2]
+            .duration_since(night.timestamps[1])
+            .unwrap();
+        assert_eq!(second, hours(1));
+    }
+
+    #[test]
+    fn test_trajectory() {
+        let mut geograph = geograph_fixture();
+        geograph.tag_edge(1, 2, "canal");
+        geograph.add_edge(
+            2,
+            5,
+            EdgeAttributes {
+                geometry: Some(vec![(3.5, 3.0)]),
+                tags: vec!["canal".to_string()],
+                ..Default::default()
+            },
+        );
+        let profile =
+            SpeedProfile::new(Speed::from_kmh(100.0)).with("canal", Speed::from_kmh(20.0));
+        let options = RouteOptions::new().with_speed_profile(&profile);
+        let route = geograph.route(&(-0.5, 0.0), &(5.0, 5.5), &options);
+        let departure = UNIX_EPOCH + Duration::from_secs(1_000);
+
+        let hour = Duration::from_secs(3_600);
+        let trajectory = geograph.trajectory(&route, departure, &profile, Sampling::Time(hour));
+        let last = trajectory[trajectory.len() - 1];
+        let arrival = departure + route.duration.unwrap();
+
+        assert_eq!(route.nodes, vec![0, 1, 2, 5]);
+        assert_eq!(last.position, (5.0, 5.5));
+        assert!((last.distance.km() - route.distance.km()).abs() < 1e-9);
+        assert!(
+            last.time
+                .duration_since(arrival)
+                .unwrap_or_else(|e| e.duration())
+                < Duration::from_millis(1)
+        );
+        assert_eq!(trajectory[1].time, departure + hour);
+
+        let fixed = route.trajectory(departure, Speed::from_kmh(100.0), Sampling::Time(hour));
+        assert!(fixed.len() < trajectory.len());
+    }
+
+    #[test]
+    fn test_tag_penalty() {
+        let mut geograph = geograph_fixture();
+        let ferry = EdgeAttributes {
+            tags: vec!["ferry".to_string()],
+            ..Default::default()
+        };
+        geograph.add_edge(1, 2, ferry.clone());
+        geograph.add_edge(2, 5, ferry);
+        let avoid_ferries = TagPenalty::new(Haversine).with("ferry", 10.0);
 
-        let path = geograph.dijsktra(5, 0).unwrap();
-        assert_eq!(path, vec![5, 4, 1, 0]);
+        let route = geograph.route(
+            &(0.0, 0.0),
+            &(5.0, 5.0),
+            &RouteOptions::new().with_cost(&avoid_ferries),
+        );
+        assert_eq!(route.path_type, PathType::ViaWaypoints);
+        assert!(!route.nodes.contains(&2));
     }
 }
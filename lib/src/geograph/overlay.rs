@@ -0,0 +1,136 @@
+use std::collections::{HashMap, HashSet};
+
+use super::NodeId;
+
+/// A reversible set of closures and penalties applied on top of a geograph,
+/// used to simulate disruptions without modifying or cloning the geograph.
+///
+/// Closed nodes and edges are skipped by the route calculations using the
+/// overlay, while penalties multiply the cost of the penalized edges, or of
+/// the edges entering the penalized nodes. Edges are directed, from a node
+/// to one of its waypoints.
+#[derive(Debug, Clone, Default)]
+pub struct Overlay {
+    closed_nodes: HashSet<NodeId>,
+    closed_edges: HashSet<(NodeId, NodeId)>,
+    node_penalties: HashMap<NodeId, f64>,
+    edge_penalties: HashMap<(NodeId, NodeId), f64>,
+}
+
+impl Overlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn close_node(&mut self, id: NodeId) -> &mut Self {
+        self.closed_nodes.insert(id);
+        self
+    }
+
+    pub fn close_edge(&mut self, from: NodeId, to: NodeId) -> &mut Self {
+        self.closed_edges.insert((from, to));
+        self
+    }
+
+    /// Multiplies the cost of the edges entering the node by the given factor.
+    pub fn penalize_node(&mut self, id: NodeId, factor: f64) -> &mut Self {
+        self.node_penalties.insert(id, factor);
+        self
+    }
+
+    /// Multiplies the cost of the edge by the given factor.
+    pub fn penalize_edge(&mut self, from: NodeId, to: NodeId, factor: f64) -> &mut Self {
+        self.edge_penalties.insert((from, to), factor);
+        self
+    }
+
+    /// Removes any closure or penalty of the node.
+    pub fn restore_node(&mut self, id: NodeId) -> &mut Self {
+        self.closed_nodes.remove(&id);
+        self.node_penalties.remove(&id);
+        self
+    }
+
+    /// Removes any closure or penalty of the edge.
+    pub fn restore_edge(&mut self, from: NodeId, to: NodeId) -> &mut Self {
+        self.closed_edges.remove(&(from, to));
+        self.edge_penalties.remove(&(from, to));
+        self
+    }
+
+    /// Removes all closures and penalties.
+    pub fn reset(&mut self) -> &mut Self {
+        self.closed_nodes.clear();
+        self.closed_edges.clear();
+        self.node_penalties.clear();
+        self.edge_penalties.clear();
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.closed_nodes.is_empty()
+            && self.closed_edges.is_empty()
+            && self.node_penalties.is_empty()
+            && self.edge_penalties.is_empty()
+    }
+
+    pub fn is_node_closed(&self, id: NodeId) -> bool {
+        self.closed_nodes.contains(&id)
+    }
+
+    pub fn is_edge_closed(&self, from: NodeId, to: NodeId) -> bool {
+        self.closed_edges.contains(&(from, to)) || self.is_node_closed(to)
+    }
+
+    /// Factor multiplying the cost of the edge, combining its own penalty
+    /// and the penalty of the node it enters.
+    pub fn penalty(&self, from: NodeId, to: NodeId) -> f64 {
+        let edge_penalty = self.edge_penalties.get(&(from, to)).unwrap_or(&1.0);
+        let node_penalty = self.node_penalties.get(&to).unwrap_or(&1.0);
+        edge_penalty * node_penalty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closures() {
+        let mut overlay = Overlay::new();
+        overlay.close_node(1).close_edge(2, 3);
+
+        assert!(overlay.is_node_closed(1));
+        assert!(overlay.is_edge_closed(0, 1));
+        assert!(overlay.is_edge_closed(2, 3));
+        assert!(!overlay.is_edge_closed(3, 2));
+
+        overlay.restore_node(1);
+        assert!(!overlay.is_edge_closed(0, 1));
+    }
+
+    #[test]
+    fn test_penalties() {
+        let mut overlay = Overlay::new();
+        overlay.penalize_node(1, 2.0).penalize_edge(0, 1, 3.0);
+
+        assert_eq!(overlay.penalty(0, 1), 6.0);
+        assert_eq!(overlay.penalty(2, 1), 2.0);
+        assert_eq!(overlay.penalty(1, 0), 1.0);
+
+        overlay.restore_edge(0, 1);
+        assert_eq!(overlay.penalty(0, 1), 2.0);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut overlay = Overlay::new();
+        assert!(overlay.is_empty());
+
+        overlay.close_node(1).penalize_edge(0, 1, 3.0);
+        assert!(!overlay.is_empty());
+
+        overlay.reset();
+        assert!(overlay.is_empty());
+    }
+}
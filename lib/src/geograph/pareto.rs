@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+
+use super::label::{LabelledPath, Labels};
+use super::route::Query;
+use super::{EdgeCost, Geograph, Geolocalizable, Limit, Node, NodeId, Route, RouteOptions};
+
+/// Represents a route of the Pareto front of a multi-criteria search:
+/// no other route is better for one criterion without being worse for another.
+#[derive(Debug)]
+pub struct ParetoRoute {
+    /// The route, whose `cost` is its value for the first criterion.
+    pub route: Route,
+    /// Values of the route for each criterion, in the order of the search,
+    /// along the geograph.
+    pub criteria: Vec<f64>,
+}
+
+/// Checks if a criteria vector is at least as good as another one for all
+/// criteria. Equal vectors dominate each other, so only one is kept.
+fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a <= b)
+}
+
+impl Geograph {
+    /// Calculates the Pareto-optimal routes between two geolocations over
+    /// several criteria, such as distance and `Hops`, or distance, price and
+    /// emissions, using a multi-label correcting search.
+    ///
+    /// The nodes and edges the search can use are restricted by the options,
+    /// as with `route`, but their cost is replaced by the criteria. The routes
+    /// are sorted by their first criterion, and include their duration and
+    /// price when the options allow it.
+    ///
+    /// The front grows quickly with the number of criteria, so two or three
+    /// are advised. Returns no routes if the destination is not reachable,
+    /// and a single direct route without criteria, whose path type is
+    /// `PathType::Aborted`, if the search reaches a limit of the options.
+    ///
+    /// Panics if there are no criteria.
+    pub fn pareto_routes(
+        &self,
+        origin: &impl Geolocalizable,
+        destination: &impl Geolocalizable,
+        criteria: &[&(dyn EdgeCost + Sync)],
+        options: &RouteOptions,
+    ) -> Vec<ParetoRoute> {
+        assert!(
+            !criteria.is_empty(),
+            "A Pareto search needs at least one criterion"
+        );
+        let mut query = Query::new(self, options);
+        let allowed = |node: &Node| query.allows_node(node);
+        let (Some(entry), Some(exit)) = (
+            self.closest_where(origin, allowed),
+            self.closest_where(destination, allowed),
+        ) else {
+            return Vec::new();
+        };
+        query.set_budget(entry);
+
+        let front = match self.pareto_labels(entry.id, exit.id, criteria, &query) {
+            Ok(front) => front,
+            Err(limit) => {
+                let route = Route::aborted(origin.geoloc(), destination.geoloc(), limit);
+                return vec![ParetoRoute {
+                    route,
+                    criteria: Vec::new(),
+                }];
+            }
+        };
+        let mut routes: Vec<ParetoRoute> = front
+            .into_iter()
+            .map(|(labels, criteria)| {
+                let mut route = self.route_via(origin, destination, labels, 0.0, &query);
+                route.duration = query
+                    .speed_profile()
+                    .map(|profile| self.route_duration(&route, profile, None));
+                route.price = options.tariff.map(|tariff| tariff.price(self, &route));
+                ParetoRoute { route, criteria }
+            })
+            .collect();
+        routes.sort_by(|a, b| a.criteria.partial_cmp(&b.criteria).unwrap());
+        routes
+    }
+
+    /// Finds the non-dominated paths between two nodes, as the ids of their
+    /// nodes labelled with the first criterion, and their criteria vectors,
+    /// or the limit which aborted the search.
+    fn pareto_labels(
+        &self,
+        origin: NodeId,
+        destination: NodeId,
+        criteria: &[&(dyn EdgeCost + Sync)],
+        query: &Query,
+    ) -> Result<Vec<(LabelledPath, Vec<f64>)>, Limit> {
+        // Labels hold the values of the criteria along their path
+        let mut labels = Labels::new(origin, vec![0.0; criteria.len()], |a, b| dominates(a, b));
+        let mut queue = VecDeque::from([0]);
+
+        while let Some(current) = queue.pop_front() {
+            // Skip labels dominated since they were queued, and don't extend
+            // the paths reaching the destination
+            let label = labels.get(current);
+            let node_id = label.node;
+            if !label.alive || node_id == destination {
+                continue;
+            }
+            let Some(node) = self.graph.get(&node_id) else {
+                continue;
+            };
+            query.label(node, label.values[0])?;
+            for &neighbor_id in node.waypoints.iter() {
+                let neighbor = self.get(neighbor_id).expect("Missing neighbor");
+                let edge = self.edge_from(node, neighbor_id);
+                let Some(values) = criteria
+                    .iter()
+                    .zip(&labels.get(current).values)
+                    .map(|(criterion, value)| {
+                        Some(value + query.criterion_cost(*criterion, node, neighbor, edge)?)
+                    })
+                    .collect::<Option<Vec<f64>>>()
+                else {
+                    continue; // Impassable or excluded edge
+                };
+
+                // Discard the paths dominated at the neighbor, or by a path
+                // already reaching the destination
+                let dominated = [neighbor_id, destination]
+                    .iter()
+                    .any(|&id| labels.dominated(id, &values));
+                if dominated {
+                    continue;
+                }
+                if let Some(visitor) = query.visitor() {
+                    visitor.relax(node, neighbor, values[0]);
+                }
+                queue.push_back(labels.insert(neighbor_id, values, current));
+            }
+        }
+
+        Ok(labels
+            .take_front(destination)
+            .into_iter()
+            .map(|label| {
+                let path = labels.path(label, |criteria| criteria[0]);
+                (path, labels.get(label).values.clone())
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geograph::{Haversine, Hops, PathType, SearchLimits, SettledCounter};
+
+    /// Routes from node 0 to node 9: a short one with many hops, a long one
+    /// with few hops, one in between, and a dominated detour.
+    fn geograph_fixture() -> Geograph {
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (0.0, 0.0), vec![1, 4, 5, 7]))
+            .add(Node::new(1, (0.0, 2.5), vec![2]))
+            .add(Node::new(2, (0.0, 5.0), vec![3]))
+            .add(Node::new(3, (0.0, 7.5), vec![9]))
+            .add(Node::new(4, (5.0, 5.0), vec![9]))
+            .add(Node::new(5, (2.0, 3.0), vec![6]))
+            .add(Node::new(6, (2.0, 7.0), vec![9]))
+            .add(Node::new(7, (8.0, 5.0), vec![8]))
+            .add(Node::new(8, (8.0, 6.0), vec![9]))
+            .add(Node::new(9, (0.0, 10.0), vec![]));
+        geograph
+    }
+
+    #[test]
+    fn test_pareto_routes() {
+        let geograph = geograph_fixture();
+        let routes = geograph.pareto_routes(
+            &(0.0, 0.0),
+            &(0.0, 10.0),
+            &[&Haversine, &Hops],
+            &RouteOptions::new(),
+        );
+        let nodes: Vec<&[NodeId]> = routes.iter().map(|r| r.route.nodes.as_slice()).collect();
+
+        assert_eq!(nodes, vec![&[0, 1, 2, 3, 9][..], &[0, 5, 6, 9], &[0, 4, 9]]);
+        let hops: Vec<f64> = routes.iter().map(|route| route.criteria[1]).collect();
+        assert_eq!(hops, vec![4.0, 3.0, 2.0]);
+        for route in &routes {
+            assert!((route.route.cost - route.criteria[0]).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_pareto_routes_with_options() {
+        let geograph = geograph_fixture();
+        let options = RouteOptions::new().avoid_tag("closed");
+        let mut closed = geograph_fixture();
+        closed.tag_node(4, "closed");
+
+        let routes = closed.pareto_routes(&(0.0, 0.0), &(0.0, 10.0), &[&Hops], &options);
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].route.nodes, vec![0, 5, 6, 9]);
+
+        let unreachable = geograph.pareto_routes(&(0.0, 10.0), &(0.0, 0.0), &[&Hops], &options);
+        assert!(unreachable.is_empty());
+    }
+
+    #[test]
+    fn test_pareto_routes_with_limits() {
+        let geograph = geograph_fixture();
+        let counter = SettledCounter::new();
+        let options = RouteOptions::new().with_visitor(&counter);
+        geograph.pareto_routes(&(0.0, 0.0), &(0.0, 10.0), &[&Hops], &options);
+        assert_eq!(counter.settled(), 0);
+        assert!(counter.labelled() > 0);
+        assert!(counter.relaxed() > 0);
+
+        let options = options.with_limits(SearchLimits::new().with_max_settled(3));
+        let routes = geograph.pareto_routes(&(0.0, 0.0), &(0.0, 10.0), &[&Hops], &options);
+        assert_eq!(routes.len(), 1);
+        assert_eq!(
+            routes[0].route.path_type,
+            PathType::Aborted(Limit::MaxSettled)
+        );
+        assert!(routes[0].criteria.is_empty());
+    }
+}
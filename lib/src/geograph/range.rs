@@ -0,0 +1,215 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::budget::SearchResult;
+use super::cost::TotalCost;
+use super::label::Labels;
+use super::route::Query;
+use super::speed::edge_length;
+use super::{Geograph, Node, NodeId};
+
+/// Values of the labels of a range-constrained search.
+#[derive(Clone, Copy)]
+struct Reach {
+    cost: f64,
+    /// Distance travelled since the last station, in kilometres.
+    used: f64,
+}
+
+impl Reach {
+    /// Checks if a label is at least as good as another one, both on cost
+    /// and on distance since the last station.
+    fn beats(&self, other: &Reach) -> bool {
+        self.cost <= other.cost && self.used <= other.used
+    }
+}
+
+impl Geograph {
+    /// Determines the cheapest path between two nodes which never travels
+    /// further than the range of the query without reaching a station,
+    /// starting with a full range at the origin.
+    ///
+    /// It runs a multi-label variant of Dijkstra's algorithm, where each node
+    /// keeps the labels not beaten both on cost and on distance since the
+    /// last station. The `access` and `egress` distances, in kilometres,
+    /// between the geograph and the origin and destination count against
+    /// the range.
+    pub(crate) fn range_search(
+        &self,
+        origin: NodeId,
+        destination: NodeId,
+        start: f64,
+        (access, egress): (f64, f64),
+        query: &Query,
+    ) -> SearchResult {
+        let Some(range) = query.range() else {
+            return Ok(None);
+        };
+        let refuel = |id: NodeId, used: f64| if query.is_station(id) { 0.0 } else { used };
+        if access > range {
+            return Ok(None);
+        }
+        let reach = Reach {
+            cost: start,
+            used: refuel(origin, access),
+        };
+        let mut labels = Labels::new(origin, reach, Reach::beats);
+        let mut queue = BinaryHeap::from([Reverse((TotalCost(start), 0))]);
+
+        while let Some(Reverse((cost, current))) = queue.pop() {
+            let label = labels.get(current);
+            let (node, used) = (label.node, label.values.used);
+            // Skip labels beaten since they were queued
+            if !label.alive {
+                continue;
+            }
+            if let Some(node) = self.graph.get(&node) {
+                query.label(node, cost.0)?;
+            }
+            // Early exit once the destination is reached with enough range left
+            if node == destination && used + egress <= range {
+                let path = labels.path(current, |reach| reach.cost);
+                if let Some(visitor) = query.visitor() {
+                    visitor.found(&path);
+                }
+                return Ok(Some(path));
+            }
+
+            let Some(node) = self.graph.get(&node) else {
+                continue;
+            };
+            for &neighbor_id in node.waypoints.iter() {
+                let neighbor = self.get(neighbor_id).expect("Missing neighbor");
+                let edge = self.edge_from(node, neighbor_id);
+                let Some(additional_cost) = query.edge_cost(node, neighbor, edge, cost.0) else {
+                    continue; // Impassable or excluded edge
+                };
+                let used = used + edge_length(node, neighbor, edge).km();
+                if used > range {
+                    continue; // Out of range before reaching the neighbor
+                }
+                let reach = Reach {
+                    cost: cost.0 + additional_cost,
+                    used: refuel(neighbor_id, used),
+                };
+                if labels.dominated(neighbor_id, &reach) {
+                    continue;
+                }
+                if let Some(visitor) = query.visitor() {
+                    visitor.relax(node, neighbor, reach.cost);
+                }
+                let label = labels.insert(neighbor_id, reach, current);
+                queue.push(Reverse((TotalCost(reach.cost), label)));
+            }
+        }
+
+        Ok(None) // If no path within range is found
+    }
+
+    /// Chooses the stations to stop at along a path within range, as few as
+    /// possible: each stop is the furthest station reachable from the previous
+    /// one, until the destination is within range.
+    pub(crate) fn refuelling_stops(
+        &self,
+        nodes: &[NodeId],
+        (access, egress): (f64, f64),
+        query: &Query,
+    ) -> Vec<NodeId> {
+        let Some(range) = query.range() else {
+            return Vec::new();
+        };
+        let nodes: Vec<&Node> = nodes.iter().filter_map(|&id| self.get(id)).collect();
+        // Distance travelled when reaching each node, from the origin
+        let mut reached = vec![access];
+        for pair in nodes.windows(2) {
+            let edge = self.edge_from(pair[0], pair[1].id);
+            reached.push(reached[reached.len() - 1] + edge_length(pair[0], pair[1], edge).km());
+        }
+        let Some(&arrival) = reached.last() else {
+            return Vec::new();
+        };
+        let arrival = arrival + egress;
+
+        let mut stops = Vec::new();
+        let mut refuelled = 0.0;
+        while arrival - refuelled > range {
+            let next = nodes.iter().zip(&reached).rev().find(|(node, &at)| {
+                at > refuelled && at - refuelled <= range && query.is_station(node.id)
+            });
+            let Some((node, &at)) = next else {
+                break; // Not within range, which the search prevents
+            };
+            stops.push(node.id);
+            refuelled = at;
+        }
+        stops
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geograph::{Distance, PathType, RouteOptions};
+
+    /// A line along the equator, one degree apart, with a detour north
+    /// through node 5.
+    fn geograph_fixture() -> Geograph {
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (0.0, 0.0), vec![1]))
+            .add(Node::new(1, (0.0, 1.0), vec![0, 2, 5]))
+            .add(Node::new(2, (0.0, 2.0), vec![1, 3]))
+            .add(Node::new(3, (0.0, 3.0), vec![2, 4, 5]))
+            .add(Node::new(4, (0.0, 4.0), vec![3]))
+            .add(Node::new(5, (1.0, 2.0), vec![1, 3]));
+        geograph
+    }
+
+    fn options(km: f64) -> RouteOptions<'static> {
+        RouteOptions::new()
+            .with_range(Distance::from_km(km))
+            .refuel_at("charging_station")
+    }
+
+    #[test]
+    fn test_route_within_range() {
+        let mut geograph = geograph_fixture();
+        for id in [1, 2, 3] {
+            geograph.tag_node(id, "charging_station");
+        }
+
+        let route = geograph.route(&(0.0, 0.0), &(0.0, 4.0), &options(250.0));
+        assert_eq!(route.nodes, vec![0, 1, 2, 3, 4]);
+        // Node 2 is reached within range, and the destination from it
+        assert_eq!(route.stations, vec![2]);
+
+        let route = geograph.route(&(0.0, 0.0), &(0.0, 4.0), &RouteOptions::new());
+        assert!(route.stations.is_empty());
+    }
+
+    #[test]
+    fn test_detour_to_station() {
+        let mut geograph = geograph_fixture();
+        geograph.tag_node(5, "charging_station");
+
+        let route = geograph.route(&(0.0, 0.0), &(0.0, 4.0), &options(300.0));
+        assert_eq!(route.nodes, vec![0, 1, 5, 3, 4]);
+        assert_eq!(route.stations, vec![5]);
+
+        let route = geograph.route(&(0.0, 0.0), &(0.0, 4.0), &options(100.0));
+        assert_eq!(route.path_type, PathType::Direct);
+    }
+
+    #[test]
+    fn test_range_includes_access() {
+        let mut geograph = geograph_fixture();
+        geograph.tag_node(2, "charging_station");
+
+        // The origin is about 111 km away from node 0
+        let route = geograph.route(&(-1.0, 0.0), &(0.0, 2.0), &options(250.0));
+        assert_eq!(route.path_type, PathType::Direct);
+        let route = geograph.route(&(-1.0, 0.0), &(0.0, 2.0), &options(400.0));
+        assert_eq!(route.nodes, vec![0, 1, 2]);
+        assert!(route.stations.is_empty());
+    }
+}
@@ -0,0 +1,547 @@
+use std::iter;
+use std::time::{Duration, SystemTime};
+
+use super::area::{Corridor, Exclusions};
+use super::budget::{Budget, Limit, SearchLimits};
+use super::calendar::{epoch_seconds, SECONDS_PER_HOUR};
+use super::tariff::TariffCost;
+use super::trajectory;
+use super::visitor::SearchVisitor;
+use super::{
+    Date, Distance, EdgeAttributes, EdgeCost, Geograph, Geoloc, Haversine, Node, NodeId, Overlay,
+    Path, PathType, Polygon, Sampling, ShortestPath, Speed, SpeedProfile, Tariff, TrajectoryPoint,
+    ValidityWindow, Vessel,
+};
+
+/// Represents the result of a route calculation.
+#[derive(Debug)]
+pub struct Route {
+    /// Geometric length of the route, including the legs from the origin to
+    /// the geograph and from the geograph to the destination.
+    pub distance: Distance,
+    /// Cost of the route along the geograph, as evaluated by the `EdgeCost`
+    /// used for the calculation. It is zero for direct routes.
+    pub cost: f64,
+    /// Geolocations along the route, including the shape of its edges.
+    pub path: Path<Geoloc>,
+    pub path_type: PathType,
+    /// Ids of the geograph nodes traversed by the route.
+    pub nodes: Vec<NodeId>,
+    /// Estimated travel time, including the dwell times at the via stops.
+    /// Only known when a `SpeedProfile` is set on the geograph or the options.
+    pub duration: Option<Duration>,
+    /// Estimated arrival time, for the routes with a departure time.
+    pub arrival: Option<SystemTime>,
+    /// Times at which the route reaches each of its `nodes`,
+    /// for the routes with a departure time.
+    pub timestamps: Vec<SystemTime>,
+    /// Price of the route, for the routes with a `Tariff` set in the options.
+    pub price: Option<f64>,
+    /// Ids of the stations where the route stops to refuel,
+    /// for the routes with a range set in the options.
+    pub stations: Vec<NodeId>,
+}
+
+impl Route {
+    /// Builds a direct route between the origin and destination,
+    /// used when no route is found along the geograph.
+    pub(crate) fn direct(origin: Geoloc, destination: Geoloc) -> Self {
+        let path = Path::from(vec![origin, destination]);
+        Self {
+            distance: path.length(),
+            cost: 0.0,
+            path,
+            path_type: PathType::Direct,
+            nodes: Vec::new(),
+            duration: None,
+            arrival: None,
+            timestamps: Vec::new(),
+            price: None,
+            stations: Vec::new(),
+        }
+    }
+
+    /// Builds a direct route between the origin and destination,
+    /// used when the search is aborted on reaching one of its limits.
+    pub(crate) fn aborted(origin: Geoloc, destination: Geoloc, limit: Limit) -> Self {
+        Self {
+            path_type: PathType::Aborted(limit),
+            ..Self::direct(origin, destination)
+        }
+    }
+}
+
+impl Route {
+    /// Generates the timestamped trajectory of the route, departing at the
+    /// given time and travelling at a fixed speed, sampled at regular intervals
+    /// of time or distance. Its positions follow the great circles between the
+    /// points of the route.
+    pub fn trajectory(
+        &self,
+        departure: SystemTime,
+        speed: Speed,
+        sampling: Sampling,
+    ) -> Vec<TrajectoryPoint> {
+        trajectory::sample(&self.path, iter::repeat(speed), departure, sampling)
+    }
+
+    /// Joins the routes between consecutive stops into a single route,
+    /// adding the dwell time at every intermediate stop to its duration.
+    pub(crate) fn join(legs: impl IntoIterator<Item = Route>, dwell: Duration) -> Self {
+        let mut legs = legs.into_iter();
+        let mut route = legs.next().expect("A route needs at least one leg");
+        for leg in legs {
+            route.distance += leg.distance;
+            route.cost += leg.cost;
+            route.path.extend(leg.path.iter().skip(1));
+            // Aborted legs abort the whole route
+            match (route.path_type, leg.path_type) {
+                (PathType::Aborted(_), _) | (_, PathType::Direct) => {}
+                (_, path_type) => route.path_type = path_type,
+            }
+            let junction = route.nodes.last().is_some() && route.nodes.last() == leg.nodes.first();
+            route
+                .nodes
+                .extend(leg.nodes.into_iter().skip(junction as usize));
+            route.stations.extend(leg.stations);
+            route.duration = route
+                .duration
+                .zip(leg.duration)
+                .map(|(duration, leg)| duration.saturating_add(dwell).saturating_add(leg));
+        }
+        route
+    }
+}
+
+impl From<Route> for ShortestPath {
+    fn from(route: Route) -> ShortestPath {
+        (route.distance, route.path, route.path_type)
+    }
+}
+
+/// Algorithm of the route searches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Search {
+    /// Delta-stepping on large geographs when several threads are idle,
+    /// with the `concurrency` feature, and Dijkstra's algorithm otherwise.
+    #[default]
+    Auto,
+    Dijkstra,
+    /// Parallel delta-stepping, with the `concurrency` feature. It finds the
+    /// same cost as Dijkstra's algorithm, but may break ties between routes
+    /// of equal cost differently. It only pays off on single long queries
+    /// over large geographs with idle threads, not within batches, which
+    /// already run their searches in parallel.
+    #[cfg(feature = "concurrency")]
+    DeltaStepping,
+}
+
+/// Options to customize a route calculation.
+#[derive(Clone)]
+pub struct RouteOptions<'a> {
+    pub(crate) cost: &'a (dyn EdgeCost + Sync),
+    pub(crate) avoid: Vec<Polygon>,
+    pub(crate) overlay: Option<&'a Overlay>,
+    pub(crate) avoid_tags: Vec<String>,
+    pub(crate) vessel: Option<Vessel>,
+    pub(crate) departure: Option<Date>,
+    pub(crate) departure_time: Option<SystemTime>,
+    pub(crate) speed_profile: Option<&'a SpeedProfile>,
+    pub(crate) fastest: bool,
+    pub(crate) tariff: Option<&'a Tariff>,
+    pub(crate) cheapest: bool,
+    pub(crate) range: Option<Distance>,
+    pub(crate) stations: Vec<String>,
+    pub(crate) corridor: Option<f64>,
+    pub(crate) search: Search,
+    pub(crate) visitor: Option<&'a (dyn SearchVisitor + Sync)>,
+    pub(crate) limits: SearchLimits,
+    pub(crate) dwell: Duration,
+}
+
+impl<'a> RouteOptions<'a> {
+    pub fn new() -> Self {
+        Self {
+            cost: &Haversine,
+            avoid: Vec::new(),
+            overlay: None,
+            avoid_tags: Vec::new(),
+            vessel: None,
+            departure: None,
+            departure_time: None,
+            speed_profile: None,
+            fastest: false,
+            tariff: None,
+            cheapest: false,
+            range: None,
+            stations: Vec::new(),
+            corridor: None,
+            search: Search::Auto,
+            visitor: None,
+            limits: SearchLimits::new(),
+            dwell: Duration::ZERO,
+        }
+    }
+
+    /// Sets the cost minimized by the route calculation.
+    /// Defaults to the haversine distance.
+    pub fn with_cost(mut self, cost: &'a (dyn EdgeCost + Sync)) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /// Adds an exclusion zone: nodes inside it and edges crossing it
+    /// are skipped by the route calculation.
+    pub fn avoid(mut self, area: Polygon) -> Self {
+        self.avoid.push(area);
+        self
+    }
+
+    /// Skips the nodes and edges with the given tag.
+    pub fn avoid_tag(mut self, tag: &str) -> Self {
+        self.avoid_tags.push(tag.to_string());
+        self
+    }
+
+    /// Sets the vessel of the route, which skips the edges whose limits
+    /// it exceeds.
+    pub fn for_vessel(mut self, vessel: Vessel) -> Self {
+        self.vessel = Some(vessel);
+        self
+    }
+
+    /// Sets the departure date of the route, which skips the nodes and edges
+    /// closed on that date. Without it, validity windows are not checked.
+    pub fn departing_on(mut self, date: Date) -> Self {
+        self.departure = Some(date);
+        self
+    }
+
+    /// Sets the departure time of the route, which minimizes the arrival time
+    /// using the travel time functions of the edges, or the speed profile for
+    /// the edges without one, and reports the time each node is reached.
+    /// Its date is the departure date, unless set with `departing_on`.
+    ///
+    /// Without a speed profile on the geograph or the options, the departure
+    /// time is ignored.
+    pub fn departing_at(mut self, time: SystemTime) -> Self {
+        self.departure_time = Some(time);
+        self
+    }
+
+    /// Sets the speeds used to estimate the duration of the route,
+    /// instead of the default speed profile of the geograph.
+    pub fn with_speed_profile(mut self, profile: &'a SpeedProfile) -> Self {
+        self.speed_profile = Some(profile);
+        self
+    }
+
+    /// Minimizes the travel time of the route instead of its cost, using the
+    /// speed profile of the options or the geograph. Without any speed profile,
+    /// the cost is minimized.
+    pub fn fastest(mut self) -> Self {
+        self.fastest = true;
+        self
+    }
+
+    /// Sets the tariff used to report the price of the route.
+    pub fn with_tariff(mut self, tariff: &'a Tariff) -> Self {
+        self.tariff = Some(tariff);
+        self
+    }
+
+    /// Minimizes the price of the route instead of its cost, using the tariff
+    /// of the options, even if the fastest route is requested. Without a tariff,
+    /// the cost is minimized. Time-dependent routes always minimize their
+    /// arrival time.
+    pub fn cheapest(mut self) -> Self {
+        self.cheapest = true;
+        self
+    }
+
+    /// Sets the maximum distance travelled without stopping at a station,
+    /// such as the range of an electric truck, starting with a full range
+    /// at the origin. Routes which can't stay within range aren't found.
+    pub fn with_range(mut self, range: Distance) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Allows the route to refuel at the nodes with the given tag,
+    /// such as `charging_station` or `bunkering`, when a range is set.
+    pub fn refuel_at(mut self, tag: &str) -> Self {
+        self.stations.push(tag.to_string());
+        self
+    }
+
+    /// Restricts the search to the nodes whose haversine detour, going from
+    /// the origin to the node and then to the destination, stays within a
+    /// factor of the direct distance, such as `1.5`. It speeds up the search
+    /// on geographs where routes rarely go far out of the way, such as roads
+    /// and railways, but may miss a cheaper route outside of the corridor.
+    ///
+    /// When no route is found within the corridor, the search is retried with
+    /// a larger factor, and finally without any corridor.
+    pub fn within_corridor(mut self, factor: f64) -> Self {
+        self.corridor = Some(factor);
+        self
+    }
+
+    /// Sets the search algorithm, picked automatically by default.
+    /// Range-constrained routes always use their own search.
+    pub fn with_search(mut self, search: Search) -> Self {
+        self.search = search;
+        self
+    }
+
+    /// Sets a visitor observing the searches, such as a `SettledCounter`
+    /// or a `FrontierRecorder`.
+    pub fn with_visitor(mut self, visitor: &'a (dyn SearchVisitor + Sync)) -> Self {
+        self.visitor = Some(visitor);
+        self
+    }
+
+    /// Sets the limits of the searches, such as a deadline or a cancellation
+    /// token. Routes whose search reaches a limit are `PathType::Aborted`.
+    pub fn with_limits(mut self, limits: SearchLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the time spent at every intermediate stop of a route through
+    /// several stops, added to its duration.
+    pub fn with_dwell(mut self, dwell: Duration) -> Self {
+        self.dwell = dwell;
+        self
+    }
+
+    /// Sets the closures and penalties applied on top of the geograph.
+    pub fn with_overlay(mut self, overlay: &'a Overlay) -> Self {
+        self.overlay = Some(overlay);
+        self
+    }
+}
+
+impl Default for RouteOptions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// State of a route calculation, precomputed once per query from its
+/// options, which decides the nodes and edges the search can use.
+pub(crate) struct Query<'a> {
+    geograph: &'a Geograph,
+    cost: &'a (dyn EdgeCost + Sync),
+    exclusions: Exclusions<'a>,
+    overlay: Option<&'a Overlay>,
+    avoid_tags: &'a [String],
+    vessel: Option<&'a Vessel>,
+    departure: Option<Date>,
+    speed_profile: Option<&'a SpeedProfile>,
+    #[cfg(feature = "concurrency")]
+    search: Search,
+    visitor: Option<&'a (dyn SearchVisitor + Sync)>,
+    limits: &'a SearchLimits,
+    /// Work done by the searches, once the origin node is known.
+    budget: Option<Budget<'a>>,
+    /// Ellipse restricting the nodes of the search, for the queries within a corridor.
+    corridor: Option<Corridor>,
+    /// Range, in kilometres, and tags of the stations of range-constrained queries.
+    range: Option<(f64, &'a [String])>,
+    /// Price of the edges, for the queries minimizing it.
+    tariff: Option<TariffCost<'a>>,
+    /// Departure time and speed profile of time-dependent queries.
+    timed: Option<(f64, &'a SpeedProfile)>,
+}
+
+impl<'a> Query<'a> {
+    pub fn new(geograph: &'a Geograph, options: &'a RouteOptions) -> Self {
+        let speed_profile = options.speed_profile.or(geograph.speed_profile());
+        Self {
+            geograph,
+            cost: match speed_profile {
+                Some(profile) if options.fastest => profile,
+                _ => options.cost,
+            },
+            exclusions: Exclusions::new(&options.avoid),
+            // Skip overlay lookups altogether when there is nothing to apply
+            overlay: options.overlay.filter(|overlay| !overlay.is_empty()),
+            avoid_tags: &options.avoid_tags,
+            vessel: options.vessel.as_ref(),
+            departure: options.departure.or(options.departure_time.map(Date::from)),
+            speed_profile,
+            #[cfg(feature = "concurrency")]
+            search: options.search,
+            visitor: options.visitor,
+            limits: &options.limits,
+            budget: None,
+            corridor: None,
+            range: options
+                .range
+                .map(|range| (range.km(), options.stations.as_slice())),
+            tariff: options
+                .tariff
+                .filter(|_| options.cheapest)
+                .map(|tariff| tariff.edge_cost(geograph)),
+            timed: options.departure_time.map(epoch_seconds).zip(speed_profile),
+        }
+    }
+
+    pub fn speed_profile(&self) -> Option<&'a SpeedProfile> {
+        self.speed_profile
+    }
+
+    /// Departure time, in seconds since the epoch, and speed profile
+    /// of time-dependent queries.
+    pub fn timed(&self) -> Option<(f64, &'a SpeedProfile)> {
+        self.timed
+    }
+
+    pub fn visitor(&self) -> Option<&'a (dyn SearchVisitor + Sync)> {
+        self.visitor
+    }
+
+    /// Starts counting the work of the searches from the origin node
+    /// against the limits of the query.
+    pub fn set_budget(&mut self, origin: &Node) {
+        self.budget = Some(Budget::new(self.limits, origin));
+    }
+
+    /// Notifies the visitor of a node settled by the search, and counts it
+    /// against the limits of the query, returning the limit reached if any.
+    pub fn settle(&self, node: &Node, cost: f64) -> Result<(), Limit> {
+        if let Some(visitor) = self.visitor {
+            visitor.settle(node, cost);
+        }
+        self.budget
+            .as_ref()
+            .map_or(Ok(()), |budget| budget.settle(node))
+    }
+
+    /// Notifies the visitor of a label extended by a multi-label search, and
+    /// counts it against the limits of the query like a settled node.
+    pub fn label(&self, node: &Node, cost: f64) -> Result<(), Limit> {
+        if let Some(visitor) = self.visitor {
+            visitor.label(node, cost);
+        }
+        self.budget
+            .as_ref()
+            .map_or(Ok(()), |budget| budget.settle(node))
+    }
+
+    /// Range, in kilometres, of range-constrained queries.
+    pub fn range(&self) -> Option<f64> {
+        self.range.map(|(range, _)| range)
+    }
+
+    /// Checks if the query can refuel at a node.
+    pub fn is_station(&self, id: NodeId) -> bool {
+        self.range
+            .is_some_and(|(_, tags)| tags.iter().any(|tag| self.geograph.has_node_tag(id, tag)))
+    }
+
+    /// Checks if the search should use delta-stepping.
+    #[cfg(feature = "concurrency")]
+    pub fn delta_stepping(&self) -> bool {
+        match self.search {
+            Search::Auto => crate::concurrency::prefers_delta_stepping(self.geograph),
+            Search::Dijkstra => false,
+            Search::DeltaStepping => true,
+        }
+    }
+
+    /// Restricts the nodes of the search to those inside the corridor, if any.
+    pub fn set_corridor(&mut self, corridor: Option<Corridor>) {
+        self.corridor = corridor;
+    }
+
+    pub fn allows_node(&self, node: &Node) -> bool {
+        self.corridor.is_none_or(|corridor| corridor.contains(node))
+            && !self.exclusions.excludes_node(node)
+            && !self
+                .overlay
+                .is_some_and(|overlay| overlay.is_node_closed(node.id))
+            && !self
+                .avoid_tags
+                .iter()
+                .any(|tag| self.geograph.has_node_tag(node.id, tag))
+            && self.departure.is_none_or(|date| {
+                ValidityWindow::any_contains(self.geograph.node_windows(node.id), date)
+            })
+    }
+
+    /// Evaluates the cost of going from a node to one of its waypoints,
+    /// or `None` if the search can't use the edge.
+    ///
+    /// The cost of time-dependent queries is the travel time, in seconds,
+    /// when entering the edge once `elapsed` seconds have passed since the
+    /// departure. Other queries ignore `elapsed`.
+    pub fn edge_cost(
+        &self,
+        from: &Node,
+        to: &Node,
+        edge: Option<&EdgeAttributes>,
+        elapsed: f64,
+    ) -> Option<f64> {
+        if !self.allows_edge(from, to, edge) {
+            return None;
+        }
+        let travel_time = edge.and_then(|edge| edge.travel_time.as_ref());
+        let cost = match (self.timed, travel_time) {
+            (Some((departure, _)), Some(function)) => function.seconds_at(departure + elapsed),
+            (Some((_, profile)), None) => profile.cost(from, to, edge)? * SECONDS_PER_HOUR,
+            (None, _) => match &self.tariff {
+                Some(tariff) => tariff.cost(from, to, edge)?,
+                None => self.cost.cost(from, to, edge)?,
+            },
+        };
+        self.penalize(from, to, cost)
+    }
+
+    /// Evaluates a criterion other than the cost of the query, such as those
+    /// of multi-criteria searches, for going from a node to one of its
+    /// waypoints, or `None` if the search can't use the edge.
+    pub fn criterion_cost(
+        &self,
+        criterion: &(dyn EdgeCost + Sync),
+        from: &Node,
+        to: &Node,
+        edge: Option<&EdgeAttributes>,
+    ) -> Option<f64> {
+        if !self.allows_edge(from, to, edge) {
+            return None;
+        }
+        self.penalize(from, to, criterion.cost(from, to, edge)?)
+    }
+
+    fn allows_edge(&self, from: &Node, to: &Node, edge: Option<&EdgeAttributes>) -> bool {
+        let geometry = edge.and_then(|edge| edge.geometry.as_deref());
+        if !self.allows_node(to) || self.exclusions.excludes_edge(from, to, geometry) {
+            return false;
+        }
+        let Some(edge) = edge else {
+            return true;
+        };
+        !self.avoid_tags.iter().any(|tag| edge.has_tag(tag))
+            && edge
+                .limits
+                .as_ref()
+                .zip(self.vessel)
+                .is_none_or(|(limits, vessel)| limits.allows(vessel))
+            && self
+                .departure
+                .is_none_or(|date| ValidityWindow::any_contains(&edge.validity, date))
+    }
+
+    /// Applies the overlay to the cost of an edge, filtering out closed edges
+    /// and invalid costs.
+    fn penalize(&self, from: &Node, to: &Node, cost: f64) -> Option<f64> {
+        let cost = match self.overlay {
+            Some(overlay) if overlay.is_edge_closed(from.id, to.id) => return None,
+            Some(overlay) => cost * overlay.penalty(from.id, to.id),
+            None => cost,
+        };
+        Some(cost).filter(|cost| cost.is_finite() && *cost >= 0.0)
+    }
+}
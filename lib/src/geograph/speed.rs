@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Div;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::calendar::SECONDS_PER_HOUR;
+use super::distance::{KM_PER_MILE, KM_PER_NAUTICAL_MILE};
+use super::{Distance, EdgeAttributes, EdgeCost, Geoloc, Geolocalizable, Node};
+
+/// Represents a travel speed.
+///
+/// Speeds are stored in kilometres per hour, but can be created from and
+/// read as knots or miles per hour.
+///
+/// When serialized, a speed is represented as a number of kilometres per hour.
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct Speed(f64);
+
+impl Speed {
+    pub fn from_kmh(kmh: f64) -> Self {
+        Self(kmh)
+    }
+
+    pub fn from_knots(knots: f64) -> Self {
+        Self(knots * KM_PER_NAUTICAL_MILE)
+    }
+
+    pub fn from_mph(mph: f64) -> Self {
+        Self(mph * KM_PER_MILE)
+    }
+
+    pub fn kmh(&self) -> f64 {
+        self.0
+    }
+
+    pub fn knots(&self) -> f64 {
+        self.0 / KM_PER_NAUTICAL_MILE
+    }
+
+    pub fn mph(&self) -> f64 {
+        self.0 / KM_PER_MILE
+    }
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)?;
+        f.write_str(" km/h")
+    }
+}
+
+/// Time to travel a distance at a speed.
+/// Non-positive speeds and non-finite distances never arrive.
+impl Div<Speed> for Distance {
+    type Output = Duration;
+
+    fn div(self, speed: Speed) -> Duration {
+        Duration::try_from_secs_f64(self.km() / speed.kmh() * SECONDS_PER_HOUR)
+            .ok()
+            .filter(|_| speed.kmh() > 0.0)
+            .unwrap_or(Duration::MAX)
+    }
+}
+
+/// Travel speeds along a geograph: a default speed, such as the service speed
+/// of a vessel, and optional overrides for the edges with a given tag, such as
+/// a slower speed on `ferry` edges.
+///
+/// It is an `EdgeCost` measuring the travel time of edges, in hours, so that
+/// routes using it as their cost are optimized for time.
+#[derive(Debug, Clone)]
+pub struct SpeedProfile {
+    default: Speed,
+    tags: HashMap<String, Speed>,
+}
+
+impl SpeedProfile {
+    pub fn new(default: Speed) -> Self {
+        Self {
+            default,
+            tags: HashMap::new(),
+        }
+    }
+
+    /// Sets the speed of the edges with the given tag.
+    pub fn with(mut self, tag: &str, speed: Speed) -> Self {
+        self.tags.insert(tag.to_string(), speed);
+        self
+    }
+
+    pub fn default_speed(&self) -> Speed {
+        self.default
+    }
+
+    /// Speed along an edge. When the edge has several overridden tags,
+    /// the slowest of their speeds is used.
+    pub fn speed(&self, edge: Option<&EdgeAttributes>) -> Speed {
+        edge.into_iter()
+            .flat_map(|edge| edge.tags.iter())
+            .filter_map(|tag| self.tags.get(tag).copied())
+            .reduce(|slowest, speed| if speed < slowest { speed } else { slowest })
+            .unwrap_or(self.default)
+    }
+
+    /// Time to travel from a node to one of its waypoints,
+    /// following the shape of the edge if it has one.
+    pub fn edge_duration(&self, from: &Node, to: &Node, edge: Option<&EdgeAttributes>) -> Duration {
+        edge_length(from, to, edge) / self.speed(edge)
+    }
+}
+
+impl EdgeCost for SpeedProfile {
+    fn cost(&self, from: &Node, to: &Node, edge: Option<&EdgeAttributes>) -> Option<f64> {
+        Some(edge_length(from, to, edge).km() / self.speed(edge).kmh())
+    }
+}
+
+/// Length of an edge, following its shape if it has one.
+pub(crate) fn edge_length(from: &Node, to: &Node, edge: Option<&EdgeAttributes>) -> Distance {
+    let geometry: &[Geoloc] = edge
+        .and_then(|edge| edge.geometry.as_deref())
+        .unwrap_or_default();
+    let mut length = 0.0;
+    let mut previous = from.geoloc();
+    for point in geometry.iter().chain([&to.geoloc()]) {
+        length += previous.haversine(point);
+        previous = *point;
+    }
+    Distance::from_km(length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speed_units() {
+        let speed = Speed::from_knots(10.0);
+
+        assert_eq!(speed.kmh(), 18.52);
+        assert!((speed.knots() - 10.0).abs() < 1e-12);
+        assert!((Speed::from_mph(60.0).kmh() - 96.56064).abs() < 1e-9);
+        assert_eq!(Speed::from_kmh(80.0).to_string(), "80 km/h");
+    }
+
+    #[test]
+    fn test_travel_time() {
+        assert_eq!(
+            Distance::from_km(150.0) / Speed::from_kmh(60.0),
+            Duration::from_secs(9_000)
+        );
+        assert_eq!(Distance::from_km(1.0) / Speed::from_kmh(0.0), Duration::MAX);
+    }
+
+    #[test]
+    fn test_speed_profile() {
+        let profile = SpeedProfile::new(Speed::from_kmh(80.0))
+            .with("ferry", Speed::from_kmh(30.0))
+            .with("urban", Speed::from_kmh(50.0));
+        let edge = |tags: &[&str]| EdgeAttributes {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(profile.speed(None), Speed::from_kmh(80.0));
+        assert_eq!(profile.speed(Some(&edge(&["toll"]))), Speed::from_kmh(80.0));
+        assert_eq!(
+            profile.speed(Some(&edge(&["urban", "ferry"]))),
+            Speed::from_kmh(30.0)
+        );
+
+        let from = Node::new(0, (0.0, 0.0), vec![1]);
+        let to = Node::new(1, (0.0, 1.0), vec![0]);
+        let hours = profile.cost(&from, &to, None).unwrap();
+        assert_eq!(hours, from.haversine(&to) / 80.0);
+        assert_eq!(
+            profile.edge_duration(&from, &to, None),
+            Duration::from_secs_f64(hours * SECONDS_PER_HOUR)
+        );
+    }
+}
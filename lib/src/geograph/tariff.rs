@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet};
+
+use super::speed::edge_length;
+use super::{EdgeAttributes, EdgeCost, Geograph, Node, NodeId, Route};
+
+/// Monetary cost of moving cargo with a transport mode, in any currency.
+///
+/// The price of a route adds up:
+/// - A fixed cost, charged once per route
+/// - A cost per kilometre travelled
+/// - Fees for the named passages traversed, such as canal tolls, charged
+///   once per passage on the edges tagged with its name, even if the route
+///   enters the passage several times
+/// - Handling charges at the nodes where the route starts and ends,
+///   such as port or terminal charges at transfer points
+///
+/// Only the cost per kilometre and the passage fees depend on the path,
+/// so they are the ones minimized by the `cheapest` route option. Its
+/// searches charge the fees on every entry into a passage, which steers
+/// them away from leaving and entering passages again.
+#[derive(Debug, Clone, Default)]
+pub struct Tariff {
+    fixed: f64,
+    per_km: f64,
+    passage_fees: HashMap<String, f64>,
+    handling: HashMap<NodeId, f64>,
+    tag_handling: HashMap<String, f64>,
+}
+
+impl Tariff {
+    pub fn new(fixed: f64, per_km: f64) -> Self {
+        Self {
+            fixed,
+            per_km,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the fee of the passage along the edges with the given tag.
+    pub fn with_passage_fee(mut self, tag: &str, fee: f64) -> Self {
+        self.passage_fees.insert(tag.to_string(), fee);
+        self
+    }
+
+    /// Sets the handling charge at a node.
+    pub fn with_handling(mut self, node: NodeId, charge: f64) -> Self {
+        self.handling.insert(node, charge);
+        self
+    }
+
+    /// Sets the handling charge at the nodes with the given tag, such as `port`.
+    /// Charges set for a node take precedence over those of its tags.
+    pub fn with_tag_handling(mut self, tag: &str, charge: f64) -> Self {
+        self.tag_handling.insert(tag.to_string(), charge);
+        self
+    }
+
+    pub fn fixed(&self) -> f64 {
+        self.fixed
+    }
+
+    pub fn per_km(&self) -> f64 {
+        self.per_km
+    }
+
+    /// Handling charge at a node of the geograph. When the node has several
+    /// tags with a charge, the highest one is used.
+    pub fn handling(&self, geograph: &Geograph, node: NodeId) -> f64 {
+        self.handling.get(&node).copied().unwrap_or_else(|| {
+            geograph
+                .node_tags(node)
+                .iter()
+                .filter_map(|tag| self.tag_handling.get(tag).copied())
+                .fold(0.0, f64::max)
+        })
+    }
+
+    /// Passages with a fee entered by going from a node to one of its waypoints:
+    /// those tagged on the edge but not on its origin node.
+    fn entered_passages<'a>(
+        &'a self,
+        geograph: &'a Geograph,
+        from: NodeId,
+        edge: &'a EdgeAttributes,
+    ) -> impl Iterator<Item = &'a String> {
+        edge.tags.iter().filter(move |tag| {
+            self.passage_fees.contains_key(*tag) && !geograph.has_node_tag(from, tag)
+        })
+    }
+
+    /// Fees charged for going from a node to one of its waypoints: those of the
+    /// passages the edge enters.
+    fn passage_fees(&self, geograph: &Geograph, from: NodeId, edge: &EdgeAttributes) -> f64 {
+        self.entered_passages(geograph, from, edge)
+            .map(|tag| self.passage_fees[tag])
+            .sum()
+    }
+
+    /// Price of a route along the geograph.
+    pub fn price(&self, geograph: &Geograph, route: &Route) -> f64 {
+        let handling: f64 = match (route.nodes.first(), route.nodes.last()) {
+            (Some(&first), Some(&last)) if first == last => self.handling(geograph, first),
+            (Some(&first), Some(&last)) => {
+                self.handling(geograph, first) + self.handling(geograph, last)
+            }
+            _ => 0.0,
+        };
+        // Each passage is charged once, however many times it is entered
+        let passages: HashSet<&String> = route
+            .nodes
+            .windows(2)
+            .filter_map(|pair| Some((pair[0], geograph.edge(pair[0], pair[1])?)))
+            .flat_map(|(from, edge)| self.entered_passages(geograph, from, edge))
+            .collect();
+        let fees: f64 = passages.iter().map(|tag| self.passage_fees[*tag]).sum();
+        self.fixed + self.per_km * route.distance.km() + fees + handling
+    }
+
+    /// Edge cost minimizing the price of routes along the geograph.
+    pub(crate) fn edge_cost<'a>(&'a self, geograph: &'a Geograph) -> TariffCost<'a> {
+        TariffCost {
+            tariff: self,
+            geograph,
+        }
+    }
+}
+
+/// Price of the edges of a geograph, from the cost per kilometre
+/// of a tariff and the fees of the passages they enter.
+pub(crate) struct TariffCost<'a> {
+    tariff: &'a Tariff,
+    geograph: &'a Geograph,
+}
+
+impl EdgeCost for TariffCost<'_> {
+    fn cost(&self, from: &Node, to: &Node, edge: Option<&EdgeAttributes>) -> Option<f64> {
+        let fees = edge.map_or(0.0, |edge| {
+            self.tariff.passage_fees(self.geograph, from.id, edge)
+        });
+        Some(self.tariff.per_km * edge_length(from, to, edge).km() + fees)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geograph::{Distance, Geolocalizable};
+
+    fn geograph_fixture() -> Geograph {
+        // A canal between nodes 1 and 3, through node 2
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (0.0, 0.0), vec![1]))
+            .add(Node::new(1, (0.0, 1.0), vec![0, 2]))
+            .add(Node::new(2, (0.0, 2.0), vec![1, 3]))
+            .add(Node::new(3, (0.0, 3.0), vec![2]))
+            .tag_node(2, "canal")
+            .tag_node(0, "port")
+            .tag_node(3, "port")
+            .tag_edge(1, 2, "canal")
+            .tag_edge(2, 3, "canal");
+        geograph
+    }
+
+    #[test]
+    fn test_price() {
+        let geograph = geograph_fixture();
+        let tariff = Tariff::new(100.0, 2.0)
+            .with_passage_fee("canal", 1_000.0)
+            .with_tag_handling("port", 50.0)
+            .with_handling(3, 80.0);
+        let mut route = Route::direct((0.0, 0.0), (0.0, 3.0));
+        route.distance = Distance::from_km(300.0);
+        route.nodes = vec![0, 1, 2, 3];
+
+        // The canal is charged once, when entered from node 1
+        assert_eq!(
+            tariff.price(&geograph, &route),
+            100.0 + 600.0 + 1_000.0 + 130.0
+        );
+        assert_eq!(tariff.handling(&geograph, 1), 0.0);
+
+        // Leaving the canal and entering it again doesn't charge it twice
+        route.nodes = vec![0, 1, 2, 1, 2, 3];
+        assert_eq!(
+            tariff.price(&geograph, &route),
+            100.0 + 600.0 + 1_000.0 + 130.0
+        );
+
+        route.nodes = Vec::new();
+        assert_eq!(tariff.price(&geograph, &route), 700.0);
+    }
+
+    #[test]
+    fn test_tariff_cost() {
+        let geograph = geograph_fixture();
+        let tariff = Tariff::new(100.0, 2.0).with_passage_fee("canal", 1_000.0);
+        let cost = tariff.edge_cost(&geograph);
+        let node = |id| geograph.get(id).unwrap();
+        let edge = |from, to| cost.cost(node(from), node(to), geograph.edge(from, to));
+        let km = node(0).haversine(node(1));
+
+        assert!((edge(0, 1).unwrap() - 2.0 * km).abs() < 1e-9);
+        assert!((edge(1, 2).unwrap() - 2.0 * km - 1_000.0).abs() < 1e-9);
+        assert!((edge(2, 3).unwrap() - 2.0 * km).abs() < 1e-9);
+    }
+}
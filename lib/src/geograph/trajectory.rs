@@ -0,0 +1,162 @@
+use std::time::{Duration, SystemTime};
+
+use super::calendar::SECONDS_PER_HOUR;
+use super::{Distance, Geoloc, Geolocalizable, Speed};
+
+/// Interval between the points of a trajectory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sampling {
+    Time(Duration),
+    Distance(Distance),
+}
+
+/// Represents a point of a trajectory: where the route is at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrajectoryPoint {
+    pub time: SystemTime,
+    pub position: Geoloc,
+    /// Direction of travel, in degrees clockwise from the north.
+    pub heading: f64,
+    /// Distance travelled since the departure.
+    pub distance: Distance,
+}
+
+/// Samples the positions along a path, travelled from the departure time
+/// at the given speed for each of its segments, following great circles.
+///
+/// The first and last points of the path are always included. Sampling stops
+/// at the first segment that can't be travelled, with a non-positive speed.
+pub(crate) fn sample(
+    path: &[Geoloc],
+    speeds: impl IntoIterator<Item = Speed>,
+    departure: SystemTime,
+    sampling: Sampling,
+) -> Vec<TrajectoryPoint> {
+    let step = match sampling {
+        Sampling::Time(interval) => interval.as_secs_f64(),
+        Sampling::Distance(interval) => interval.km(),
+    };
+    let point = |position: Geoloc, heading: f64, elapsed: f64, travelled: f64| TrajectoryPoint {
+        time: departure + Duration::from_secs_f64(elapsed),
+        position,
+        heading,
+        distance: Distance::from_km(travelled),
+    };
+    let segments: Vec<(Geoloc, Geoloc, Speed)> = path
+        .windows(2)
+        .zip(speeds)
+        .map(|(pair, speed)| (pair[0], pair[1], speed))
+        // Skip repeated locations, which have no heading
+        .filter(|(from, to, _)| from.haversine(to) > 0.0)
+        .collect();
+
+    let Some(&(start, next, _)) = segments.first() else {
+        return path
+            .first()
+            .map(|&position| point(position, 0.0, 0.0, 0.0))
+            .into_iter()
+            .collect();
+    };
+    let mut points = vec![point(start, start.bearing(&next), 0.0, 0.0)];
+    let (mut elapsed, mut travelled, mut samples) = (0.0, 0.0, 1.0);
+    let (mut end, mut heading) = (start, 0.0);
+
+    for (from, to, speed) in segments {
+        let length = from.haversine(&to);
+        let duration = length / speed.kmh() * SECONDS_PER_HOUR;
+        if !(duration.is_finite() && duration > 0.0) {
+            break;
+        }
+        let (offset, span) = match sampling {
+            Sampling::Time(_) => (elapsed, duration),
+            Sampling::Distance(_) => (travelled, length),
+        };
+        // Heading on arrival at the end of the segment
+        heading = (to.bearing(&from) + 180.0) % 360.0;
+
+        while step > 0.0 && samples * step < offset + span {
+            let fraction = (samples * step - offset) / span;
+            let position = from.interpolate(&to, fraction);
+            points.push(point(
+                position,
+                position.bearing(&to),
+                elapsed + fraction * duration,
+                travelled + fraction * length,
+            ));
+            samples += 1.0;
+        }
+        elapsed += duration;
+        travelled += length;
+        end = to;
+    }
+
+    if travelled > 0.0 {
+        points.push(point(end, heading, elapsed, travelled));
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn speeds(kmh: f64) -> impl Iterator<Item = Speed> {
+        std::iter::repeat(Speed::from_kmh(kmh))
+    }
+
+    #[test]
+    fn test_sample_by_time() {
+        // About 111 km along the equator, then 111 km north
+        let path = [(0.0, 0.0), (0.0, 1.0), (1.0, 1.0)];
+        let hour = Duration::from_secs(3_600);
+        let points = sample(&path, speeds(50.0), UNIX_EPOCH, Sampling::Time(hour));
+
+        assert_eq!(points.len(), 6);
+        assert_eq!(points[0].position, (0.0, 0.0));
+        assert_eq!(points[0].heading, 90.0);
+        assert_eq!(points[1].time, UNIX_EPOCH + hour);
+        assert!((points[1].distance.km() - 50.0).abs() < 1e-9);
+        assert!((points[1].position.haversine(&(0.0, 0.0)) - 50.0).abs() < 1e-9);
+        assert!((points[3].heading - 0.0).abs() < 1e-9);
+
+        let last = points[points.len() - 1];
+        assert_eq!(last.position, (1.0, 1.0));
+        assert!((last.distance.km() - (0.0, 0.0).haversine(&(0.0, 1.0)) * 2.0).abs() < 1e-6);
+        assert!(points.windows(2).all(|pair| pair[0].time < pair[1].time));
+    }
+
+    #[test]
+    fn test_sample_by_distance() {
+        let path = [(0.0, 0.0), (0.0, 1.0)];
+        let points = sample(
+            &path,
+            speeds(100.0),
+            UNIX_EPOCH,
+            Sampling::Distance(Distance::from_km(25.0)),
+        );
+
+        assert_eq!(points.len(), 6);
+        assert!((points[2].distance.km() - 50.0).abs() < 1e-9);
+        assert_eq!(points[2].time, UNIX_EPOCH + Duration::from_secs(1_800));
+    }
+
+    #[test]
+    fn test_sample_degenerate_paths() {
+        let points = sample(
+            &[(1.0, 1.0), (1.0, 1.0)],
+            speeds(10.0),
+            UNIX_EPOCH,
+            Sampling::Time(Duration::from_secs(60)),
+        );
+        assert_eq!(points.len(), 1);
+
+        let points = sample(
+            &[(0.0, 0.0), (0.0, 1.0)],
+            speeds(0.0),
+            UNIX_EPOCH,
+            Sampling::Time(Duration::from_secs(60)),
+        );
+        assert_eq!(points.len(), 1);
+    }
+}
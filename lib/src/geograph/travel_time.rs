@@ -0,0 +1,188 @@
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use super::calendar::{epoch_seconds, SECONDS_PER_DAY};
+
+/// Represents the errors of an invalid travel time function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TravelTimeError {
+    /// The function has no breakpoints, or a zero period.
+    Empty,
+    /// The breakpoints are not sorted by strictly increasing entry time,
+    /// or lie outside of the period.
+    Unsorted,
+    /// Entering later can arrive earlier, which breaks the FIFO property
+    /// that time-dependent routing relies on.
+    NotFifo,
+}
+
+impl fmt::Display for TravelTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TravelTimeError::Empty => write!(f, "travel time function without breakpoints"),
+            TravelTimeError::Unsorted => {
+                write!(f, "travel time breakpoints not sorted within the period")
+            }
+            TravelTimeError::NotFifo => write!(f, "entering later can arrive earlier"),
+        }
+    }
+}
+
+impl std::error::Error for TravelTimeError {}
+
+/// Travel time of an edge as a function of the time it is entered, such as
+/// a slower road during rush hours, or a port access only open at high tide.
+///
+/// It is periodic and piecewise-linear: breakpoints give the travel time at
+/// an offset within the period, UTC, and the travel time between them is
+/// interpolated, wrapping around from the last breakpoint to the first one.
+/// Waiting for a window to open is modelled by a travel time decreasing one
+/// second per second.
+///
+/// Functions must be FIFO: entering an edge later never arrives earlier,
+/// that is, the travel time never decreases faster than time passes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TravelTimeFunction {
+    period: f64,
+    /// Offsets within the period and travel times, in seconds.
+    points: Vec<(f64, f64)>,
+}
+
+impl TravelTimeFunction {
+    pub fn new(period: Duration, points: &[(Duration, Duration)]) -> Result<Self, TravelTimeError> {
+        let period = period.as_secs_f64();
+        let points: Vec<(f64, f64)> = points
+            .iter()
+            .map(|(offset, travel_time)| (offset.as_secs_f64(), travel_time.as_secs_f64()))
+            .collect();
+        let (Some(first), Some(last)) = (points.first(), points.last()) else {
+            return Err(TravelTimeError::Empty);
+        };
+        if period == 0.0 {
+            return Err(TravelTimeError::Empty);
+        }
+        if last.0 >= period || points.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err(TravelTimeError::Unsorted);
+        }
+
+        let wrapped = (first.0 + period, first.1);
+        let fifo = points
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .chain([(*last, wrapped)])
+            .all(|((t0, tt0), (t1, tt1))| t0 + tt0 <= t1 + tt1);
+        if !fifo {
+            return Err(TravelTimeError::NotFifo);
+        }
+        Ok(Self { period, points })
+    }
+
+    /// Travel time function with a period of a day, from the time of day
+    /// of each breakpoint.
+    pub fn daily(points: &[(Duration, Duration)]) -> Result<Self, TravelTimeError> {
+        Self::new(Duration::from_secs_f64(SECONDS_PER_DAY), points)
+    }
+
+    pub fn constant(travel_time: Duration) -> Self {
+        Self {
+            period: SECONDS_PER_DAY,
+            points: vec![(0.0, travel_time.as_secs_f64())],
+        }
+    }
+
+    /// Travel time when entering the edge at the given time.
+    pub fn travel_time(&self, entry: SystemTime) -> Duration {
+        Duration::from_secs_f64(self.seconds_at(epoch_seconds(entry)))
+    }
+
+    /// Travel time, in seconds, when entering at the given seconds since the epoch.
+    pub(crate) fn seconds_at(&self, entry: f64) -> f64 {
+        let offset = entry.rem_euclid(self.period);
+        let next = self.points.partition_point(|&(t, _)| t <= offset);
+        let (t0, tt0) = match next {
+            0 => {
+                let (t, tt) = self.points[self.points.len() - 1];
+                (t - self.period, tt)
+            }
+            _ => self.points[next - 1],
+        };
+        let (t1, tt1) = match self.points.get(next) {
+            Some(&point) => point,
+            None => (self.points[0].0 + self.period, self.points[0].1),
+        };
+        tt0 + (tt1 - tt0) * (offset - t0) / (t1 - t0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geograph::calendar::SECONDS_PER_HOUR;
+    use std::time::UNIX_EPOCH;
+
+    fn hours(hours: f64) -> Duration {
+        Duration::from_secs_f64(hours * SECONDS_PER_HOUR)
+    }
+
+    fn rush_hours() -> TravelTimeFunction {
+        TravelTimeFunction::daily(&[
+            (hours(6.0), hours(1.0)),
+            (hours(8.0), hours(2.0)),
+            (hours(10.0), hours(1.0)),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_travel_time() {
+        let function = rush_hours();
+        let at = |h: f64| function.travel_time(UNIX_EPOCH + hours(24.0 * 365.0 + h));
+
+        assert_eq!(at(6.0), hours(1.0));
+        assert_eq!(at(7.0), hours(1.5));
+        assert_eq!(at(8.0), hours(2.0));
+        assert_eq!(at(12.0), hours(1.0));
+        assert_eq!(at(3.0), hours(1.0));
+        assert_eq!(
+            TravelTimeFunction::constant(hours(3.0)).travel_time(UNIX_EPOCH),
+            hours(3.0)
+        );
+    }
+
+    #[test]
+    fn test_tidal_window() {
+        // Open from 12:00 to 14:00, taking 1 hour, waiting for the opening otherwise
+        let function = TravelTimeFunction::daily(&[
+            (hours(0.0), hours(13.0)),
+            (hours(12.0), hours(1.0)),
+            (hours(14.0), hours(1.0)),
+            (
+                hours(14.0) + Duration::from_secs(1),
+                hours(23.0) - Duration::from_secs(1),
+            ),
+        ])
+        .unwrap();
+        let at = |h: f64| function.seconds_at(h * SECONDS_PER_HOUR) / SECONDS_PER_HOUR;
+
+        assert!((at(6.0) - 7.0).abs() < 1e-6);
+        assert!((at(13.0) - 1.0).abs() < 1e-6);
+        assert!((at(18.0) - 19.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_functions() {
+        assert_eq!(TravelTimeFunction::daily(&[]), Err(TravelTimeError::Empty));
+        assert_eq!(
+            TravelTimeFunction::daily(&[(hours(8.0), hours(1.0)), (hours(6.0), hours(1.0))]),
+            Err(TravelTimeError::Unsorted)
+        );
+        assert_eq!(
+            TravelTimeFunction::daily(&[(hours(25.0), hours(1.0))]),
+            Err(TravelTimeError::Unsorted)
+        );
+        assert_eq!(
+            TravelTimeFunction::daily(&[(hours(8.0), hours(3.0)), (hours(9.0), hours(1.0))]),
+            Err(TravelTimeError::NotFifo)
+        );
+    }
+}
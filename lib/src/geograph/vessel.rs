@@ -0,0 +1,108 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Size classes of vessels, ordered from the smallest to the largest,
+/// as commonly used for the largest vessel a passage can take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum SizeClass {
+    Feeder,
+    Handysize,
+    Panamax,
+    NeoPanamax,
+    Suezmax,
+    Capesize,
+    Vlcc,
+    Ulcc,
+}
+
+/// Dimensions of a vessel, in metres, and its size class.
+/// Unknown dimensions are not checked against the edge limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vessel {
+    pub draft: Option<f64>,
+    pub beam: Option<f64>,
+    pub length: Option<f64>,
+    pub class: Option<SizeClass>,
+}
+
+/// Largest vessel dimensions, in metres, and size class an edge can take.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EdgeLimits {
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub max_draft: Option<f64>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub max_beam: Option<f64>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub max_length: Option<f64>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub max_class: Option<SizeClass>,
+}
+
+impl EdgeLimits {
+    /// Checks if the vessel fits within the limits.
+    pub fn allows(&self, vessel: &Vessel) -> bool {
+        fn fits<T: PartialOrd>(value: Option<T>, max: Option<T>) -> bool {
+            match (value, max) {
+                (Some(value), Some(max)) => value <= max,
+                _ => true,
+            }
+        }
+
+        fits(vessel.draft, self.max_draft)
+            && fits(vessel.beam, self.max_beam)
+            && fits(vessel.length, self.max_length)
+            && fits(vessel.class, self.max_class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let panama = EdgeLimits {
+            max_draft: Some(15.2),
+            max_beam: Some(51.25),
+            max_class: Some(SizeClass::NeoPanamax),
+            ..Default::default()
+        };
+        let feeder = Vessel {
+            draft: Some(8.0),
+            class: Some(SizeClass::Feeder),
+            ..Default::default()
+        };
+        let capesize = Vessel {
+            class: Some(SizeClass::Capesize),
+            ..Default::default()
+        };
+        let deep = Vessel {
+            draft: Some(16.0),
+            ..Default::default()
+        };
+
+        assert!(panama.allows(&feeder));
+        assert!(panama.allows(&Vessel::default()));
+        assert!(!panama.allows(&capesize));
+        assert!(!panama.allows(&deep));
+        assert!(EdgeLimits::default().allows(&capesize));
+    }
+}
@@ -0,0 +1,263 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use super::{Geoloc, Geolocalizable, Node, NodeId};
+
+/// A trait for observing route searches, to trace or visualize them.
+///
+/// The search calls it when it settles a node, whose cost from the origin is
+/// then final, when it relaxes an edge improving the cost of reaching a node,
+/// and when it finds the path to the destination. Multi-label searches, such
+/// as `pareto_routes` and range-constrained routes, keep several paths to each
+/// node, so they never settle nodes: they call `label` instead whenever they
+/// extend one of those paths. Costs are those minimized by the search. All
+/// methods do nothing by default.
+///
+/// Visitors are shared by the searches running concurrently with the same
+/// route options, so they record what they observe with interior mutability.
+pub trait SearchVisitor {
+    fn settle(&self, _node: &Node, _cost: f64) {}
+
+    fn relax(&self, _from: &Node, _to: &Node, _cost: f64) {}
+
+    /// Called by multi-label searches when they extend a path reaching the
+    /// node, whose cost may not be final. A node may be labelled many times.
+    fn label(&self, _node: &Node, _cost: f64) {}
+
+    /// Called with the ids of the nodes along the path, labelled with their cost.
+    fn found(&self, _path: &[(NodeId, f64)]) {}
+}
+
+/// Counts the nodes settled, the edges relaxed and the labels extended by
+/// searches.
+#[derive(Debug, Default)]
+pub struct SettledCounter {
+    settled: AtomicUsize,
+    relaxed: AtomicUsize,
+    labelled: AtomicUsize,
+}
+
+impl SettledCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn settled(&self) -> usize {
+        self.settled.load(Ordering::Relaxed)
+    }
+
+    pub fn relaxed(&self) -> usize {
+        self.relaxed.load(Ordering::Relaxed)
+    }
+
+    pub fn labelled(&self) -> usize {
+        self.labelled.load(Ordering::Relaxed)
+    }
+
+    pub fn reset(&self) {
+        self.settled.store(0, Ordering::Relaxed);
+        self.relaxed.store(0, Ordering::Relaxed);
+        self.labelled.store(0, Ordering::Relaxed);
+    }
+}
+
+impl SearchVisitor for SettledCounter {
+    fn settle(&self, _: &Node, _: f64) {
+        self.settled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn relax(&self, _: &Node, _: &Node, _: f64) {
+        self.relaxed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn label(&self, _: &Node, _: f64) {
+        self.labelled.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Kinds of events of the search frontier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontierEventKind {
+    /// The node joins the frontier, or its cost improves.
+    Reached,
+    /// The node leaves the frontier, with its final cost.
+    Settled,
+}
+
+/// Represents a change of the search frontier: the nodes reached
+/// but not settled yet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrontierEvent {
+    /// Index of the event, in the order the search made them.
+    pub step: usize,
+    pub kind: FrontierEventKind,
+    pub node: NodeId,
+    pub geoloc: Geoloc,
+    pub cost: f64,
+}
+
+/// Records the search frontier over time, and the path found, to be
+/// replayed or exported as GeoJSON.
+#[derive(Debug, Default)]
+pub struct FrontierRecorder {
+    events: Mutex<Vec<FrontierEvent>>,
+    path: Mutex<Vec<NodeId>>,
+}
+
+impl FrontierRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<FrontierEvent> {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Ids of the nodes along the last path found.
+    pub fn path(&self) -> Vec<NodeId> {
+        self.path
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Ids of the nodes on the frontier after the given number of events.
+    pub fn frontier_at(&self, step: usize) -> Vec<NodeId> {
+        let mut frontier: Vec<NodeId> = Vec::new();
+        for event in self.events().iter().take(step) {
+            frontier.retain(|&node| node != event.node);
+            if event.kind == FrontierEventKind::Reached {
+                frontier.push(event.node);
+            }
+        }
+        frontier
+    }
+
+    /// Exports the events as a GeoJSON feature collection of points, with
+    /// their `step`, `event`, `node` and `cost` as properties, followed by
+    /// the path found as a line string, if any.
+    pub fn to_geojson(&self) -> String {
+        let events = self.events();
+        let mut features: Vec<String> = events
+            .iter()
+            .map(|event| {
+                let kind = match event.kind {
+                    FrontierEventKind::Reached => "reached",
+                    FrontierEventKind::Settled => "settled",
+                };
+                format!(
+                    r#"{{"type":"Feature","geometry":{{"type":"Point","coordinates":[{},{}]}},"properties":{{"step":{},"event":"{}","node":{},"cost":{}}}}}"#,
+                    event.geoloc.lng(),
+                    event.geoloc.lat(),
+                    event.step,
+                    kind,
+                    event.node,
+                    event.cost,
+                )
+            })
+            .collect();
+
+        let path = self.path();
+        let coordinates: Vec<String> = path
+            .iter()
+            .filter_map(|node| events.iter().find(|event| event.node == *node))
+            .map(|event| format!("[{},{}]", event.geoloc.lng(), event.geoloc.lat()))
+            .collect();
+        if coordinates.len() > 1 {
+            features.push(format!(
+                r#"{{"type":"Feature","geometry":{{"type":"LineString","coordinates":[{}]}},"properties":{{"event":"path"}}}}"#,
+                coordinates.join(",")
+            ));
+        }
+
+        format!(
+            r#"{{"type":"FeatureCollection","features":[{}]}}"#,
+            features.join(",")
+        )
+    }
+
+    fn record(&self, kind: FrontierEventKind, node: &Node, cost: f64) {
+        let mut events = self.events.lock().unwrap_or_else(PoisonError::into_inner);
+        let step = events.len();
+        events.push(FrontierEvent {
+            step,
+            kind,
+            node: node.id,
+            geoloc: node.geoloc(),
+            cost,
+        });
+    }
+}
+
+impl SearchVisitor for FrontierRecorder {
+    fn settle(&self, node: &Node, cost: f64) {
+        self.record(FrontierEventKind::Settled, node, cost);
+    }
+
+    fn relax(&self, _: &Node, to: &Node, cost: f64) {
+        self.record(FrontierEventKind::Reached, to, cost);
+    }
+
+    fn found(&self, path: &[(NodeId, f64)]) {
+        *self.path.lock().unwrap_or_else(PoisonError::into_inner) =
+            path.iter().map(|&(node, _)| node).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geograph::{Geograph, RouteOptions};
+
+    fn geograph_fixture() -> Geograph {
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (0.0, 0.0), vec![1, 3]))
+            .add(Node::new(1, (0.0, 1.0), vec![0, 2]))
+            .add(Node::new(2, (0.0, 2.0), vec![1]))
+            .add(Node::new(3, (0.5, 0.0), vec![0]));
+        geograph
+    }
+
+    #[test]
+    fn test_settled_counter() {
+        let geograph = geograph_fixture();
+        let counter = SettledCounter::new();
+        let options = RouteOptions::new().with_visitor(&counter);
+
+        geograph.route(&(0.0, 0.0), &(0.0, 2.0), &options);
+        // Node 3 is settled before node 2, being closer to the origin
+        assert_eq!(counter.settled(), 4);
+        assert_eq!(counter.relaxed(), 3);
+
+        counter.reset();
+        geograph.route(&(0.0, 0.0), &(0.0, 0.0), &options);
+        assert_eq!(counter.settled(), 1);
+    }
+
+    #[test]
+    fn test_frontier_recorder() {
+        let geograph = geograph_fixture();
+        let recorder = FrontierRecorder::new();
+        let options = RouteOptions::new().with_visitor(&recorder);
+
+        geograph.route(&(0.0, 0.0), &(0.0, 2.0), &options);
+        let events = recorder.events();
+        assert_eq!(events[0].kind, FrontierEventKind::Settled);
+        assert_eq!(events[0].node, 0);
+        assert_eq!(recorder.frontier_at(3), vec![1, 3]);
+        assert_eq!(recorder.frontier_at(events.len()), Vec::<NodeId>::new());
+        assert_eq!(recorder.path(), vec![0, 1, 2]);
+
+        let geojson = recorder.to_geojson();
+        assert!(geojson.starts_with(r#"{"type":"FeatureCollection","features":[{"#));
+        assert!(geojson.contains(
+            r#""geometry":{"type":"Point","coordinates":[1,0]},"properties":{"step":1,"event":"reached","node":1"#
+        ));
+        assert!(geojson.contains(r#"{"type":"LineString","coordinates":[[0,0],[1,0],[2,0]]}"#));
+        assert!(geojson.ends_with("]}"));
+    }
+}
@@ -2,7 +2,12 @@ use crate::{build_geograph_mod, json, prelude::*};
 use lazy_static::lazy_static;
 use routrs_highways_dataset;
 
-build_geograph_mod!(serde_json::from_str::<json::JsonGeograph>(
-    &routrs_highways_dataset::HIGHWAYS_JSON
-)
-.expect("Failed to parse Highways Geograph JSON"));
+build_geograph_mod!({
+    let mut geograph: Geograph =
+        serde_json::from_str::<json::JsonGeograph>(&routrs_highways_dataset::HIGHWAYS_JSON)
+            .expect("Failed to parse Highways Geograph JSON")
+            .into();
+    // Average speed of a truck, including traffic
+    geograph.set_speed_profile(SpeedProfile::new(Speed::from_kmh(70.0)));
+    geograph
+});
@@ -1,12 +1,42 @@
-use serde::{Deserialize, Serialize};
+mod v2;
+mod validation;
 
-use crate::geograph::{Geograph, Node, NodeId};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::geograph::{Geograph, Node, NodeId, ValidityWindow};
+
+pub use v2::*;
+pub use validation::*;
+
+/// A node of a JSON geograph.
+/// Its `coordinates` are ordered as `[lat, lng]`.
+///
+/// New optional fields may be added, so nodes are built with `new`.
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[non_exhaustive]
 pub struct JsonNode {
     pub id: NodeId,
     pub coordinates: Vec<f64>,
     pub waypoints: Vec<i32>,
+    /// Periods during which the node is open. Nodes without them are always open.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validity: Vec<ValidityWindow>,
+}
+
+impl JsonNode {
+    pub fn new(id: NodeId, coordinates: Vec<f64>, waypoints: Vec<i32>) -> Self {
+        Self {
+            id,
+            coordinates,
+            waypoints,
+            validity: Vec::new(),
+        }
+    }
+
+    pub fn with_validity(mut self, window: ValidityWindow) -> Self {
+        self.validity.push(window);
+        self
+    }
 }
 
 impl From<JsonNode> for Node {
@@ -19,6 +49,8 @@ impl From<JsonNode> for Node {
     }
 }
 
+/// Version 1 of the JSON geograph format, as used by the bundled datasets.
+/// It carries no version field, and its coordinates are ordered as `[lat, lng]`.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct JsonGeograph {
     pub geograph: String,
@@ -29,15 +61,86 @@ impl From<JsonGeograph> for Geograph {
     fn from(json_geograph: JsonGeograph) -> Geograph {
         let mut geograph = Geograph::new(&json_geograph.geograph);
         for json_node in json_geograph.nodes {
+            for &window in &json_node.validity {
+                geograph.add_node_window(json_node.id, window);
+            }
             geograph.add(json_node.into());
         }
         geograph
     }
 }
 
+/// A JSON geograph in any of the supported format versions.
+/// The version is read from the `version` field, and documents
+/// without it are read as v1.
+#[derive(Debug, Clone)]
+pub enum VersionedJsonGeograph {
+    V1(JsonGeograph),
+    V2(JsonGeographV2),
+}
+
+impl VersionedJsonGeograph {
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        match self {
+            VersionedJsonGeograph::V1(json_geograph) => json_geograph.validate(),
+            VersionedJsonGeograph::V2(json_geograph) => json_geograph.validate(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionedJsonGeograph {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let version = match value.get("version") {
+            None => 1,
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| D::Error::custom("version must be an integer"))?,
+        };
+
+        match version {
+            1 => serde_json::from_value(value).map(VersionedJsonGeograph::V1),
+            2 => serde_json::from_value(value).map(VersionedJsonGeograph::V2),
+            _ => return Err(D::Error::custom(format!("unsupported version {version}"))),
+        }
+        .map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for VersionedJsonGeograph {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            VersionedJsonGeograph::V1(json_geograph) => json_geograph.serialize(serializer),
+            VersionedJsonGeograph::V2(json_geograph) => json_geograph.serialize(serializer),
+        }
+    }
+}
+
+impl TryFrom<VersionedJsonGeograph> for Geograph {
+    type Error = Vec<ValidationError>;
+
+    fn try_from(json_geograph: VersionedJsonGeograph) -> Result<Geograph, Self::Error> {
+        match json_geograph {
+            VersionedJsonGeograph::V1(json_geograph) => {
+                json_geograph.validate()?;
+                Ok(json_geograph.into())
+            }
+            VersionedJsonGeograph::V2(json_geograph) => json_geograph.try_into(),
+        }
+    }
+}
+
+/// Validates a JSON geograph document of any supported version,
+/// returning all the errors found.
+pub fn validate(json: &str) -> Result<(), Vec<ValidationError>> {
+    serde_json::from_str::<VersionedJsonGeograph>(json)
+        .map_err(|error| vec![ValidationError::Parse(error.to_string())])?
+        .validate()
+}
+
 #[cfg(test)]
 mod test {
-    use crate::geograph::Geolocalizable;
+    use crate::geograph::{EdgeLimits, GeolocError, Geolocalizable, SizeClass};
 
     use super::*;
 
@@ -57,6 +160,7 @@ mod test {
             id: 0,
             coordinates: vec![179.5, 51.3],
             waypoints: vec![1, 3684, 5945, 5257, 10859, 3512, 6947, 8385, 2446, 2222],
+            validity: Vec::new(),
         };
         let node: Node = json_node.clone().into();
 
@@ -64,4 +168,183 @@ mod test {
         assert_eq!(vec![node.lat(), node.lng()], *json_node.coordinates);
         assert_eq!(node.waypoints, json_node.waypoints.into());
     }
+
+    const V2_JSON: &str = r#"{
+        "version": 2,
+        "geograph": "ferries",
+        "coordinate_order": "lng_lat",
+        "metadata": {
+            "name": "Baltic ferries",
+            "source": "manual",
+            "licence": "CC-BY-4.0",
+            "bbox": {"min_lat": 53.0, "min_lng": 9.0, "max_lat": 60.0, "max_lng": 25.0}
+        },
+        "nodes": [
+            {"id": 0, "coordinates": [10.2, 54.3], "waypoints": [1]},
+            {"id": 1, "coordinates": [24.9, 59.4], "waypoints": [0], "validity": [{"start": "2024-01-01", "end": "2024-12-31"}]}
+        ],
+        "edges": [
+            {"from": 0, "to": 1, "weight": 42.0, "tags": ["ferry"], "geometry": [[18.0, 57.0]]},
+            {"from": 1, "to": 0, "limits": {"max_draft": 9.5, "max_class": "panamax"}, "validity": [{"start": "05-01", "end": "09-30"}]}
+        ]
+    }"#;
+
+    #[test]
+    fn it_reads_versioned_geographs() {
+        let v1 = r#"{"geograph": "marnet", "nodes": [{"id": 0, "coordinates": [51.3, 179.5], "waypoints": []}]}"#;
+
+        assert!(matches!(
+            serde_json::from_str(v1).unwrap(),
+            VersionedJsonGeograph::V1(_)
+        ));
+        assert!(matches!(
+            serde_json::from_str(V2_JSON).unwrap(),
+            VersionedJsonGeograph::V2(_)
+        ));
+        assert!(serde_json::from_str::<VersionedJsonGeograph>(r#"{"version": 3}"#).is_err());
+    }
+
+    #[test]
+    fn it_converts_v2_into_geograph() {
+        let json_geograph: VersionedJsonGeograph = serde_json::from_str(V2_JSON).unwrap();
+        let geograph = Geograph::try_from(json_geograph).unwrap();
+        let node = geograph.get(0).unwrap();
+        let edge = geograph.edge(0, 1).unwrap();
+
+        assert_eq!(geograph.name, "ferries");
+        assert_eq!((node.lat(), node.lng()), (54.3, 10.2));
+        assert_eq!(edge.weight, Some(42.0));
+        assert!(edge.has_tag("ferry"));
+        assert_eq!(edge.geometry, Some(vec![(57.0, 18.0)]));
+        assert!(edge.limits.is_none());
+        assert!(edge.validity.is_empty());
+        assert!(geograph.node_windows(0).is_empty());
+        assert_eq!(
+            geograph.node_windows(1),
+            [ValidityWindow::dates(
+                "2024-01-01".parse().unwrap(),
+                "2024-12-31".parse().unwrap()
+            )]
+        );
+        assert_eq!(
+            geograph.edge(1, 0).unwrap().validity,
+            [ValidityWindow::yearly(
+                "05-01".parse().unwrap(),
+                "09-30".parse().unwrap()
+            )]
+        );
+        assert_eq!(
+            geograph.edge(1, 0).unwrap().limits,
+            Some(EdgeLimits {
+                max_draft: Some(9.5),
+                max_class: Some(SizeClass::Panamax),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn it_builds_v2_documents() {
+        let json_geograph: JsonGeographV2 = serde_json::from_str(V2_JSON).unwrap();
+        let yearly = ValidityWindow::yearly("05-01".parse().unwrap(), "09-30".parse().unwrap());
+        let bbox = JsonBBox {
+            min_lat: 53.0,
+            min_lng: 9.0,
+            max_lat: 60.0,
+            max_lng: 25.0,
+        };
+
+        assert_eq!(
+            json_geograph.metadata,
+            JsonMetadata::new("Baltic ferries")
+                .with_source("manual")
+                .with_licence("CC-BY-4.0")
+                .with_bbox(bbox)
+        );
+        assert_eq!(
+            json_geograph.edges,
+            [
+                JsonEdge::new(0, 1)
+                    .with_weight(42.0)
+                    .with_tag("ferry")
+                    .with_geometry(vec![vec![18.0, 57.0]]),
+                JsonEdge::new(1, 0)
+                    .with_limits(EdgeLimits {
+                        max_draft: Some(9.5),
+                        max_class: Some(SizeClass::Panamax),
+                        ..Default::default()
+                    })
+                    .with_validity(yearly)
+            ]
+        );
+        let node = JsonNode::new(1, vec![24.9, 59.4], vec![0]).with_validity(yearly);
+        assert_eq!(node.validity, [yearly]);
+    }
+
+    #[test]
+    fn it_validates_geographs() {
+        assert_eq!(validate(V2_JSON), Ok(()));
+
+        let swapped = V2_JSON.replace("\"lng_lat\"", "\"lat_lng\"");
+        assert_eq!(
+            validate(&swapped).unwrap_err(),
+            vec![
+                ValidationError::NodeOutsideBBox(0),
+                ValidationError::NodeOutsideBBox(1)
+            ]
+        );
+
+        let out_of_range = V2_JSON.replace("[24.9, 59.4]", "[24.9, 159.4]");
+        assert_eq!(
+            validate(&out_of_range).unwrap_err(),
+            vec![ValidationError::InvalidCoordinates {
+                node: 1,
+                error: CoordinateError::Invalid(GeolocError::LatitudeOutOfRange(159.4))
+            }]
+        );
+
+        let invalid = V2_JSON
+            .replace("\"waypoints\": [0]", "\"waypoints\": [7]")
+            .replace("42.0", "-1.0");
+        let errors = validate(&invalid).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError::UnknownWaypoint {
+                    node: 1,
+                    waypoint: 7
+                },
+                ValidationError::InvalidEdgeWeight {
+                    from: 0,
+                    to: 1,
+                    weight: -1.0
+                },
+                ValidationError::UnknownEdge { from: 1, to: 0 }
+            ]
+        );
+
+        let negative_limits = V2_JSON.replace("9.5", "-9.5");
+        assert_eq!(
+            validate(&negative_limits).unwrap_err(),
+            vec![ValidationError::InvalidEdgeLimits { from: 1, to: 0 }]
+        );
+
+        assert!(matches!(
+            validate("{").unwrap_err()[..],
+            [ValidationError::Parse(_)]
+        ));
+
+        let invalid_date = V2_JSON.replace("09-30", "09-31");
+        assert!(matches!(
+            validate(&invalid_date).unwrap_err()[..],
+            [ValidationError::Parse(_)]
+        ));
+    }
+
+    #[test]
+    fn it_ships_a_json_schema() {
+        let schema: serde_json::Value = serde_json::from_str(SCHEMA_V2).unwrap();
+
+        assert_eq!(schema["properties"]["version"]["const"], 2);
+    }
 }
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+
+use super::JsonNode;
+use crate::geograph::{EdgeAttributes, EdgeLimits, Geograph, Geoloc, NodeId, ValidityWindow};
+
+/// Order of the coordinates in the `coordinates` and `geometry`
+/// arrays of a v2 JSON geograph.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinateOrder {
+    /// `[lat, lng]`, as used by the v1 format and the bundled datasets.
+    LatLng,
+    /// `[lng, lat]`, as used by GeoJSON.
+    LngLat,
+}
+
+/// Bounding box of a dataset, in degrees.
+/// A `min_lng` greater than `max_lng` denotes a box crossing the antimeridian.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct JsonBBox {
+    pub min_lat: f64,
+    pub min_lng: f64,
+    pub max_lat: f64,
+    pub max_lng: f64,
+}
+
+impl JsonBBox {
+    pub fn contains(&self, (lat, lng): Geoloc) -> bool {
+        let within_lng = if self.min_lng <= self.max_lng {
+            (self.min_lng..=self.max_lng).contains(&lng)
+        } else {
+            lng >= self.min_lng || lng <= self.max_lng
+        };
+        (self.min_lat..=self.max_lat).contains(&lat) && within_lng
+    }
+}
+
+/// Metadata of the dataset of a v2 JSON geograph.
+///
+/// New optional fields may be added, so metadata is built with `new` and
+/// the `with_*` methods.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct JsonMetadata {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub licence: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<JsonBBox>,
+}
+
+impl JsonMetadata {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            source: None,
+            licence: None,
+            bbox: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    pub fn with_licence(mut self, licence: &str) -> Self {
+        self.licence = Some(licence.to_string());
+        self
+    }
+
+    pub fn with_bbox(mut self, bbox: JsonBBox) -> Self {
+        self.bbox = Some(bbox);
+        self
+    }
+}
+
+/// Optional attributes of the edge going from a node to one of its waypoints.
+///
+/// New optional fields may be added, so edges are built with `new` and
+/// the `with_*` methods.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct JsonEdge {
+    pub from: NodeId,
+    pub to: NodeId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Intermediate shape points, in the declared coordinate order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<Vec<Vec<f64>>>,
+    /// Largest vessel dimensions, in metres, and size class the edge can take.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<EdgeLimits>,
+    /// Periods during which the edge is open. Edges without them are always open.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validity: Vec<ValidityWindow>,
+}
+
+impl JsonEdge {
+    pub fn new(from: NodeId, to: NodeId) -> Self {
+        Self {
+            from,
+            to,
+            weight: None,
+            tags: Vec::new(),
+            geometry: None,
+            limits: None,
+            validity: Vec::new(),
+        }
+    }
+
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn with_geometry(mut self, geometry: Vec<Vec<f64>>) -> Self {
+        self.geometry = Some(geometry);
+        self
+    }
+
+    pub fn with_limits(mut self, limits: EdgeLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    pub fn with_validity(mut self, window: ValidityWindow) -> Self {
+        self.validity.push(window);
+        self
+    }
+}
+
+/// Version 2 of the JSON geograph format.
+/// Compared to v1, it declares its coordinate order, carries dataset
+/// metadata, and supports optional per-edge attributes.
+///
+/// It is described by the JSON Schema document in `SCHEMA_V2`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JsonGeographV2 {
+    pub version: u32,
+    pub geograph: String,
+    pub coordinate_order: CoordinateOrder,
+    pub metadata: JsonMetadata,
+    pub nodes: Vec<JsonNode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edges: Vec<JsonEdge>,
+}
+
+impl TryFrom<JsonGeographV2> for Geograph {
+    type Error = Vec<super::ValidationError>;
+
+    fn try_from(json_geograph: JsonGeographV2) -> Result<Geograph, Self::Error> {
+        json_geograph.validate()?;
+
+        let order = json_geograph.coordinate_order;
+        let mut geograph = Geograph::new(&json_geograph.geograph);
+        for json_node in json_geograph.nodes {
+            let node = json_node.to_node(order).map_err(|error| vec![error])?;
+            geograph.add(node);
+            for window in json_node.validity {
+                geograph.add_node_window(json_node.id, window);
+            }
+        }
+        for json_edge in json_geograph.edges {
+            let geometry = json_edge
+                .geometry
+                .map(|points| {
+                    points
+                        .iter()
+                        .map(|point| super::to_geoloc(point, order))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()
+                .map_err(|error| {
+                    vec![super::ValidationError::InvalidEdgeGeometry {
+                        from: json_edge.from,
+                        to: json_edge.to,
+                        error,
+                    }]
+                })?;
+            geograph.add_edge(
+                json_edge.from,
+                json_edge.to,
+                EdgeAttributes {
+                    weight: json_edge.weight,
+                    tags: json_edge.tags,
+                    geometry,
+                    limits: json_edge.limits,
+                    validity: json_edge.validity,
+                    travel_time: None,
+                },
+            );
+        }
+        Ok(geograph)
+    }
+}
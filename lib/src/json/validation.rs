@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use super::{CoordinateOrder, JsonGeograph, JsonGeographV2, JsonNode};
+use crate::geograph::{Geoloc, GeolocError, Geolocalizable, LatLng, LngLat, Node, NodeId};
+
+/// JSON Schema document describing the v2 JSON geograph format.
+pub const SCHEMA_V2: &str = include_str!("geograph.v2.schema.json");
+
+/// Errors raised when a coordinate array can't be read as a location.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoordinateError {
+    /// The array doesn't have exactly two elements.
+    WrongLength(usize),
+    /// The coordinates are not a valid location.
+    Invalid(GeolocError),
+}
+
+impl fmt::Display for CoordinateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoordinateError::WrongLength(len) => {
+                write!(f, "expected 2 coordinates but found {len}")
+            }
+            CoordinateError::Invalid(error) => error.fmt(f),
+        }
+    }
+}
+
+/// Errors found when validating a JSON geograph.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// The document is not valid JSON or doesn't match the expected structure.
+    Parse(String),
+    UnsupportedVersion(u64),
+    InvalidBBox,
+    DuplicateNode(NodeId),
+    InvalidCoordinates {
+        node: NodeId,
+        error: CoordinateError,
+    },
+    NodeOutsideBBox(NodeId),
+    UnknownWaypoint {
+        node: NodeId,
+        waypoint: NodeId,
+    },
+    /// The edge doesn't connect a node to one of its waypoints.
+    UnknownEdge {
+        from: NodeId,
+        to: NodeId,
+    },
+    DuplicateEdge {
+        from: NodeId,
+        to: NodeId,
+    },
+    InvalidEdgeWeight {
+        from: NodeId,
+        to: NodeId,
+        weight: f64,
+    },
+    InvalidEdgeGeometry {
+        from: NodeId,
+        to: NodeId,
+        error: CoordinateError,
+    },
+    InvalidEdgeLimits {
+        from: NodeId,
+        to: NodeId,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Parse(message) => write!(f, "invalid geograph JSON: {message}"),
+            ValidationError::UnsupportedVersion(version) => {
+                write!(f, "unsupported geograph version {version}")
+            }
+            ValidationError::InvalidBBox => write!(f, "invalid metadata bbox"),
+            ValidationError::DuplicateNode(id) => write!(f, "duplicate node {id}"),
+            ValidationError::InvalidCoordinates { node, error } => {
+                write!(f, "invalid coordinates for node {node}: {error}")
+            }
+            ValidationError::NodeOutsideBBox(id) => {
+                write!(f, "node {id} is outside of the metadata bbox")
+            }
+            ValidationError::UnknownWaypoint { node, waypoint } => {
+                write!(f, "node {node} references unknown waypoint {waypoint}")
+            }
+            ValidationError::UnknownEdge { from, to } => {
+                write!(
+                    f,
+                    "edge {from} -> {to} is not declared in the node waypoints"
+                )
+            }
+            ValidationError::DuplicateEdge { from, to } => {
+                write!(f, "duplicate edge {from} -> {to}")
+            }
+            ValidationError::InvalidEdgeWeight { from, to, weight } => {
+                write!(f, "edge {from} -> {to} has invalid weight {weight}")
+            }
+            ValidationError::InvalidEdgeGeometry { from, to, error } => {
+                write!(f, "invalid geometry for edge {from} -> {to}: {error}")
+            }
+            ValidationError::InvalidEdgeLimits { from, to } => {
+                write!(f, "edge {from} -> {to} has negative or non-finite limits")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Reads a coordinate array in the given order as a validated location.
+pub(crate) fn to_geoloc(
+    coordinates: &[f64],
+    order: CoordinateOrder,
+) -> Result<Geoloc, CoordinateError> {
+    let &[first, second] = coordinates else {
+        return Err(CoordinateError::WrongLength(coordinates.len()));
+    };
+    match order {
+        CoordinateOrder::LatLng => LatLng::new(first, second).map(|loc| loc.geoloc()),
+        CoordinateOrder::LngLat => LngLat::new(first, second).map(|loc| loc.geoloc()),
+    }
+    .map_err(CoordinateError::Invalid)
+}
+
+impl JsonNode {
+    /// Converts the JSON node into a geograph node, reading its
+    /// coordinates in the given order.
+    pub fn to_node(&self, order: CoordinateOrder) -> Result<Node, ValidationError> {
+        let geoloc = to_geoloc(&self.coordinates, order).map_err(|error| {
+            ValidationError::InvalidCoordinates {
+                node: self.id,
+                error,
+            }
+        })?;
+        Ok(Node::new(self.id, geoloc, self.waypoints.iter().copied()))
+    }
+}
+
+/// Validates the nodes of a geograph: unique ids, valid coordinates
+/// and waypoints referencing existing nodes.
+fn validate_nodes(
+    nodes: &[JsonNode],
+    order: CoordinateOrder,
+    errors: &mut Vec<ValidationError>,
+) -> Vec<(NodeId, Geoloc)> {
+    let mut ids = HashSet::new();
+    for node in nodes {
+        if !ids.insert(node.id) {
+            errors.push(ValidationError::DuplicateNode(node.id));
+        }
+    }
+
+    let mut geolocs = Vec::new();
+    for node in nodes {
+        match node.to_node(order) {
+            Ok(parsed) => geolocs.push((node.id, parsed.geoloc())),
+            Err(error) => errors.push(error),
+        }
+        for &waypoint in node.waypoints.iter().filter(|id| !ids.contains(id)) {
+            errors.push(ValidationError::UnknownWaypoint {
+                node: node.id,
+                waypoint,
+            });
+        }
+    }
+    geolocs
+}
+
+impl JsonGeograph {
+    /// Validates the geograph, returning all the errors found.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        validate_nodes(&self.nodes, CoordinateOrder::LatLng, &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl JsonGeographV2 {
+    /// Validates the geograph, returning all the errors found.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        if self.version != 2 {
+            errors.push(ValidationError::UnsupportedVersion(self.version.into()));
+        }
+
+        let bbox = self.metadata.bbox.filter(|bbox| {
+            let valid = [bbox.min_lat, bbox.max_lat, bbox.min_lng, bbox.max_lng]
+                .iter()
+                .all(|coord| coord.is_finite())
+                && (-90.0..=90.0).contains(&bbox.min_lat)
+                && (-90.0..=90.0).contains(&bbox.max_lat)
+                && bbox.min_lat <= bbox.max_lat
+                && (-180.0..=180.0).contains(&bbox.min_lng)
+                && (-180.0..=180.0).contains(&bbox.max_lng);
+            if !valid {
+                errors.push(ValidationError::InvalidBBox);
+            }
+            valid
+        });
+
+        let geolocs = validate_nodes(&self.nodes, self.coordinate_order, &mut errors);
+        if let Some(bbox) = bbox {
+            for (id, _) in geolocs.iter().filter(|(_, geoloc)| !bbox.contains(*geoloc)) {
+                errors.push(ValidationError::NodeOutsideBBox(*id));
+            }
+        }
+
+        let connections: HashSet<(NodeId, NodeId)> = self
+            .nodes
+            .iter()
+            .flat_map(|node| {
+                node.waypoints
+                    .iter()
+                    .map(move |&waypoint| (node.id, waypoint))
+            })
+            .collect();
+        let mut edges = HashSet::new();
+        for edge in &self.edges {
+            let (from, to) = (edge.from, edge.to);
+            if !connections.contains(&(from, to)) {
+                errors.push(ValidationError::UnknownEdge { from, to });
+            }
+            if !edges.insert((from, to)) {
+                errors.push(ValidationError::DuplicateEdge { from, to });
+            }
+            if let Some(weight) = edge.weight.filter(|w| !w.is_finite() || *w < 0.0) {
+                errors.push(ValidationError::InvalidEdgeWeight { from, to, weight });
+            }
+            let geometry_errors = edge
+                .geometry
+                .iter()
+                .flatten()
+                .filter_map(|point| to_geoloc(point, self.coordinate_order).err());
+            for error in geometry_errors {
+                errors.push(ValidationError::InvalidEdgeGeometry { from, to, error });
+            }
+            let invalid_limits = edge.limits.is_some_and(|limits| {
+                [limits.max_draft, limits.max_beam, limits.max_length]
+                    .iter()
+                    .flatten()
+                    .any(|limit| !limit.is_finite() || *limit < 0.0)
+            });
+            if invalid_limits {
+                errors.push(ValidationError::InvalidEdgeLimits { from, to });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
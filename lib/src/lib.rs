@@ -1,4 +1,6 @@
+pub mod emissions;
 pub mod geograph;
+pub mod multimodal;
 pub mod prelude;
 
 #[cfg(feature = "highways")]
@@ -1,8 +1,27 @@
+mod passage;
+mod port;
+
 use crate::{build_geograph_mod, json, prelude::*};
 use lazy_static::lazy_static;
 use routrs_maritime_dataset;
 
-build_geograph_mod!(serde_json::from_str::<json::JsonGeograph>(
-    &routrs_maritime_dataset::MARITIME_JSON
-)
-.expect("Failed to parse Maritime Geograph JSON"));
+pub use passage::{passages_used, Passage, PassageOptions, PassageTolls};
+pub use port::{PORTS, PORT_TAG};
+
+build_geograph_mod!({
+    let mut geograph: Geograph =
+        serde_json::from_str::<json::JsonGeograph>(&routrs_maritime_dataset::MARITIME_JSON)
+            .expect("Failed to parse Maritime Geograph JSON")
+            .into();
+    passage::tag_passages(&mut geograph);
+    port::tag_ports(&mut geograph);
+    // Typical service speed of a container ship
+    geograph.set_speed_profile(SpeedProfile::new(Speed::from_knots(14.0)));
+    geograph
+});
+
+/// Lists the passages of the maritime geograph used by a route,
+/// in the order they are traversed.
+pub fn passages(route: &Route) -> Vec<Passage> {
+    passages_used(&GEOGRAPH, route)
+}
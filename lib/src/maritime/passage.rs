@@ -0,0 +1,361 @@
+use std::fmt;
+
+use crate::geograph::area::Exclusions;
+use crate::prelude::*;
+
+/// Named chokepoints of the maritime geograph, such as canals and straits.
+///
+/// The nodes inside the area of a passage are tagged with its `tag`, as are
+/// the edges from and to them, so that routes can avoid them and
+/// report the passages they use.
+///
+/// The Kiel Canal is not part of the MARNET geograph, so it has no passage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Passage {
+    Suez,
+    Panama,
+    Bosporus,
+    Dardanelles,
+    Gibraltar,
+    Dover,
+    Malacca,
+    BabElMandeb,
+    Hormuz,
+    NorthernSeaRoute,
+    NorthwestPassage,
+}
+
+impl Passage {
+    pub const ALL: [Passage; 11] = [
+        Passage::Suez,
+        Passage::Panama,
+        Passage::Bosporus,
+        Passage::Dardanelles,
+        Passage::Gibraltar,
+        Passage::Dover,
+        Passage::Malacca,
+        Passage::BabElMandeb,
+        Passage::Hormuz,
+        Passage::NorthernSeaRoute,
+        Passage::NorthwestPassage,
+    ];
+
+    /// Tag of the nodes and edges of the passage in the maritime geograph.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Passage::Suez => "suez_canal",
+            Passage::Panama => "panama_canal",
+            Passage::Bosporus => "bosporus",
+            Passage::Dardanelles => "dardanelles",
+            Passage::Gibraltar => "strait_of_gibraltar",
+            Passage::Dover => "strait_of_dover",
+            Passage::Malacca => "strait_of_malacca",
+            Passage::BabElMandeb => "bab_el_mandeb",
+            Passage::Hormuz => "strait_of_hormuz",
+            Passage::NorthernSeaRoute => "northern_sea_route",
+            Passage::NorthwestPassage => "northwest_passage",
+        }
+    }
+
+    pub fn from_tag(tag: &str) -> Option<Passage> {
+        Passage::ALL
+            .into_iter()
+            .find(|passage| passage.tag() == tag)
+    }
+
+    /// Largest vessel the passage can take, set on the edges of the passage.
+    /// Only the canals and the shallowest straits have limits.
+    pub fn limits(&self) -> Option<EdgeLimits> {
+        match self {
+            Passage::Suez => Some(EdgeLimits {
+                max_draft: Some(20.1),
+                max_beam: Some(77.5),
+                max_class: Some(SizeClass::Suezmax),
+                ..Default::default()
+            }),
+            Passage::Panama => Some(EdgeLimits {
+                max_draft: Some(15.2),
+                max_beam: Some(51.25),
+                max_length: Some(366.0),
+                max_class: Some(SizeClass::NeoPanamax),
+            }),
+            // Malaccamax
+            Passage::Malacca => Some(EdgeLimits {
+                max_draft: Some(20.5),
+                ..Default::default()
+            }),
+            _ => None,
+        }
+    }
+
+    /// Navigation season of the passage, set on the edges of the passage.
+    /// Only the Arctic routes have one, as they are icebound the rest of the year.
+    pub fn season(&self) -> Option<ValidityWindow> {
+        let (start, end) = match self {
+            Passage::NorthernSeaRoute => ((7, 1), (10, 31)),
+            Passage::NorthwestPassage => ((8, 1), (9, 30)),
+            _ => return None,
+        };
+        let month_day = |(month, day)| MonthDay::new(month, day).expect("Invalid season");
+        Some(ValidityWindow::yearly(month_day(start), month_day(end)))
+    }
+
+    /// Areas covered by the passage, as (min_lat, max_lat, min_lng, max_lng) boxes.
+    fn bounds(&self) -> &'static [(Lat, Lat, Lng, Lng)] {
+        match self {
+            Passage::Suez => &[(29.9, 31.35, 32.2, 32.65)],
+            Passage::Panama => &[(8.85, 9.3, -79.95, -79.5)],
+            Passage::Bosporus => &[(41.0, 41.22, 28.98, 29.12)],
+            Passage::Dardanelles => &[(40.02, 40.45, 26.15, 26.75)],
+            Passage::Gibraltar => &[(35.7, 36.3, -6.0, -5.2)],
+            Passage::Dover => &[(50.85, 51.25, 1.2, 1.9)],
+            Passage::Malacca => &[(1.8, 3.6, 100.0, 102.5)],
+            Passage::BabElMandeb => &[(12.4, 12.9, 43.1, 43.6)],
+            Passage::Hormuz => &[(26.0, 27.0, 56.0, 57.0)],
+            // Split at the antimeridian
+            Passage::NorthernSeaRoute => &[(66.0, 85.0, 50.0, 180.0), (66.0, 85.0, -180.0, -172.0)],
+            Passage::NorthwestPassage => &[(67.0, 80.0, -141.0, -80.0)],
+        }
+    }
+
+    pub fn areas(&self) -> Vec<Polygon> {
+        self.bounds()
+            .iter()
+            .map(|&(min_lat, max_lat, min_lng, max_lng)| {
+                // Longitudes of 180 wrap to -180, so boxes ending at the
+                // antimeridian stop right before it
+                let max_lng = if max_lng < 180.0 {
+                    max_lng
+                } else {
+                    180.0_f64.next_down()
+                };
+                let ring = [
+                    (min_lng, min_lat),
+                    (max_lng, min_lat),
+                    (max_lng, max_lat),
+                    (min_lng, max_lat),
+                ]
+                .iter()
+                .map(|&(lng, lat)| LngLat::new(lng, lat).expect("Invalid passage area"))
+                .collect();
+                Polygon::new(vec![ring])
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Passage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Passage::Suez => "Suez Canal",
+            Passage::Panama => "Panama Canal",
+            Passage::Bosporus => "Bosporus",
+            Passage::Dardanelles => "Dardanelles",
+            Passage::Gibraltar => "Strait of Gibraltar",
+            Passage::Dover => "Strait of Dover",
+            Passage::Malacca => "Strait of Malacca",
+            Passage::BabElMandeb => "Bab-el-Mandeb",
+            Passage::Hormuz => "Strait of Hormuz",
+            Passage::NorthernSeaRoute => "Northern Sea Route",
+            Passage::NorthwestPassage => "Northwest Passage",
+        })
+    }
+}
+
+/// Tags the nodes inside the passages, and the edges from and to them,
+/// setting the limits and season of the passage on those edges.
+///
+/// Only the edges touching the nodes of a passage are tagged, so the areas
+/// are drawn tight enough for no open-sea edge to cross them.
+pub(crate) fn tag_passages(geograph: &mut Geograph) {
+    for passage in Passage::ALL {
+        let areas = passage.areas();
+        let exclusions = Exclusions::new(&areas);
+
+        let graph: &Geograph = geograph;
+        let nodes: Vec<&Node> = graph
+            .iter_nodes()
+            .filter(|node| exclusions.excludes_node(*node))
+            .collect();
+        let mut edges: Vec<(NodeId, NodeId)> = nodes
+            .iter()
+            .flat_map(|node| {
+                node.waypoints.iter().flat_map(move |&id| {
+                    let back = graph
+                        .get(id)
+                        .is_some_and(|waypoint| waypoint.waypoints.contains(&node.id));
+                    [Some((node.id, id)), back.then_some((id, node.id))]
+                })
+            })
+            .flatten()
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        let nodes: Vec<NodeId> = nodes.iter().map(|node| node.id).collect();
+
+        for id in nodes {
+            geograph.tag_node(id, passage.tag());
+        }
+        for (from, to) in edges {
+            geograph.tag_edge(from, to, passage.tag());
+            if let Some(limits) = passage.limits() {
+                geograph.limit_edge(from, to, limits);
+            }
+            if let Some(season) = passage.season() {
+                geograph.add_edge_window(from, to, season);
+            }
+        }
+    }
+}
+
+/// Lists the passages used by a route, in the order they are traversed.
+pub fn passages_used(geograph: &Geograph, route: &Route) -> Vec<Passage> {
+    let mut passages: Vec<Passage> = Vec::new();
+    for pair in route.nodes.windows(2) {
+        let Some(edge) = geograph.edge(pair[0], pair[1]) else {
+            continue;
+        };
+        for passage in edge.tags.iter().filter_map(|tag| Passage::from_tag(tag)) {
+            if !passages.contains(&passage) {
+                passages.push(passage);
+            }
+        }
+    }
+    passages
+}
+
+/// Route options to avoid the passages of the maritime geograph.
+pub trait PassageOptions: Sized {
+    fn avoid_passage(self, passage: Passage) -> Self;
+
+    fn avoid_suez(self) -> Self {
+        self.avoid_passage(Passage::Suez)
+    }
+
+    fn avoid_panama(self) -> Self {
+        self.avoid_passage(Passage::Panama)
+    }
+
+    fn avoid_northern_sea_route(self) -> Self {
+        self.avoid_passage(Passage::NorthernSeaRoute)
+    }
+
+    fn avoid_northwest_passage(self) -> Self {
+        self.avoid_passage(Passage::NorthwestPassage)
+    }
+}
+
+impl PassageOptions for RouteOptions<'_> {
+    fn avoid_passage(self, passage: Passage) -> Self {
+        self.avoid_tag(passage.tag())
+    }
+}
+
+/// Tariffs charging tolls for the passages of the maritime geograph.
+pub trait PassageTolls: Sized {
+    fn with_toll(self, passage: Passage, toll: f64) -> Self;
+}
+
+impl PassageTolls for Tariff {
+    fn with_toll(self, passage: Passage, toll: f64) -> Self {
+        self.with_passage_fee(passage.tag(), toll)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geograph_fixture() -> Geograph {
+        // Mediterranean to the Red Sea, through Suez or a detour west of it
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (31.5, 32.3), vec![1, 3]))
+            .add(Node::new(1, (30.5, 32.4), vec![0, 2]))
+            .add(Node::new(2, (29.5, 32.6), vec![1, 3]))
+            .add(Node::new(3, (31.0, 20.0), vec![0, 2]));
+        tag_passages(&mut geograph);
+        geograph
+    }
+
+    #[test]
+    fn it_tags_passages() {
+        let geograph = geograph_fixture();
+
+        assert!(geograph.has_node_tag(1, "suez_canal"));
+        assert!(!geograph.has_node_tag(0, "suez_canal"));
+        assert!(geograph.edge(0, 1).unwrap().has_tag("suez_canal"));
+        assert!(geograph.edge(1, 2).unwrap().has_tag("suez_canal"));
+        assert!(geograph.edge(0, 3).is_none());
+    }
+
+    #[test]
+    fn it_reports_and_avoids_passages() {
+        let geograph = geograph_fixture();
+        let (from, to) = ((31.5, 32.3), (29.5, 32.6));
+
+        let route = geograph.route(&from, &to, &RouteOptions::default());
+        assert_eq!(passages_used(&geograph, &route), vec![Passage::Suez]);
+
+        let route = geograph.route(&from, &to, &RouteOptions::new().avoid_suez());
+        assert_eq!(route.nodes, vec![0, 3, 2]);
+        assert!(passages_used(&geograph, &route).is_empty());
+    }
+
+    #[test]
+    fn it_keeps_large_vessels_out_of_canals() {
+        let geograph = geograph_fixture();
+        let (from, to) = ((31.5, 32.3), (29.5, 32.6));
+        let route = |class| {
+            let vessel = Vessel {
+                class: Some(class),
+                ..Default::default()
+            };
+            geograph.route(&from, &to, &RouteOptions::new().for_vessel(vessel))
+        };
+
+        assert_eq!(geograph.edge(0, 1).unwrap().limits, Passage::Suez.limits());
+        assert_eq!(route(SizeClass::Suezmax).nodes, vec![0, 1, 2]);
+        assert_eq!(route(SizeClass::Capesize).nodes, vec![0, 3, 2]);
+    }
+
+    #[test]
+    fn it_prices_canal_tolls() {
+        let geograph = geograph_fixture();
+        let (from, to) = ((31.5, 32.3), (29.5, 32.6));
+        let tariff = Tariff::new(0.0, 10.0).with_toll(Passage::Suez, 50_000.0);
+
+        let route = geograph.route(&from, &to, &RouteOptions::new().with_tariff(&tariff));
+        assert_eq!(route.nodes, vec![0, 1, 2]);
+        assert_eq!(route.price, Some(10.0 * route.distance.km() + 50_000.0));
+
+        let options = RouteOptions::new().with_tariff(&tariff).cheapest();
+        let route = geograph.route(&from, &to, &options);
+        assert_eq!(route.nodes, vec![0, 3, 2]);
+        assert!(route.price < Some(50_000.0));
+    }
+
+    #[test]
+    fn it_tags_passages_up_to_the_antimeridian() {
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (73.0, 145.0), vec![]))
+            .add(Node::new(1, (69.65, 18.95), vec![]))
+            .add(Node::new(2, (70.0, 179.99), vec![]))
+            .add(Node::new(3, (66.1, -169.3), vec![]));
+        tag_passages(&mut geograph);
+
+        assert!(geograph.has_node_tag(0, "northern_sea_route"));
+        assert!(!geograph.has_node_tag(1, "northern_sea_route"));
+        assert!(geograph.has_node_tag(2, "northern_sea_route"));
+        assert!(!geograph.has_node_tag(3, "northern_sea_route"));
+    }
+
+    #[test]
+    fn it_reads_passage_tags() {
+        for passage in Passage::ALL {
+            assert_eq!(Passage::from_tag(passage.tag()), Some(passage));
+        }
+        assert_eq!(Passage::Suez.to_string(), "Suez Canal");
+    }
+}
@@ -0,0 +1,68 @@
+use crate::prelude::*;
+
+/// Tag of the nodes of the maritime geograph serving a port.
+pub const PORT_TAG: &str = "port";
+
+/// Farthest node from a port serving it, as the nodes of the maritime
+/// geograph are up to a hundred kilometres offshore.
+const PORT_MAX_DISTANCE_KM: f64 = 150.0;
+
+/// Major container ports, with the location of their terminals, served by
+/// the node of the maritime geograph closest to them.
+pub const PORTS: [(&str, Geoloc); 24] = [
+    ("Rotterdam", (51.95, 4.05)),
+    ("Antwerp", (51.28, 4.33)),
+    ("Hamburg", (53.54, 9.97)),
+    ("Le Havre", (49.48, 0.12)),
+    ("Felixstowe", (51.95, 1.31)),
+    ("Gdansk", (54.4, 18.66)),
+    ("Algeciras", (36.13, -5.43)),
+    ("Valencia", (39.44, -0.32)),
+    ("Barcelona", (41.35, 2.16)),
+    ("Marseille", (43.35, 5.3)),
+    ("Genoa", (44.4, 8.9)),
+    ("Piraeus", (37.94, 23.6)),
+    ("Jebel Ali", (25.01, 55.06)),
+    ("Nhava Sheva", (18.95, 72.95)),
+    ("Singapore", (1.26, 103.84)),
+    ("Shanghai", (31.35, 121.6)),
+    ("Hong Kong", (22.33, 114.12)),
+    ("Busan", (35.1, 129.04)),
+    ("Durban", (-29.87, 31.03)),
+    ("Santos", (-23.97, -46.3)),
+    ("New York", (40.67, -74.05)),
+    ("Savannah", (32.08, -81.09)),
+    ("Houston", (29.73, -95.27)),
+    ("Los Angeles", (33.74, -118.26)),
+];
+
+/// Tags the node closest to each port with `PORT_TAG`, if close enough.
+pub(crate) fn tag_ports(geograph: &mut Geograph) {
+    for (_, geoloc) in PORTS {
+        let closest = geograph
+            .closest_where(&geoloc, |_| true)
+            .filter(|node| node.haversine(&geoloc) <= PORT_MAX_DISTANCE_KM);
+        if let Some(node) = closest {
+            let id = node.id;
+            geograph.tag_node(id, PORT_TAG);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_tags_ports() {
+        let mut geograph = Geograph::new("Test Geograph");
+        geograph
+            .add(Node::new(0, (51.9, 3.9), vec![1]))
+            .add(Node::new(1, (30.0, -40.0), vec![0]));
+        tag_ports(&mut geograph);
+
+        // Far from every port in the middle of the Atlantic
+        assert!(geograph.has_node_tag(0, PORT_TAG));
+        assert!(!geograph.has_node_tag(1, PORT_TAG));
+    }
+}
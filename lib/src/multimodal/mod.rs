@@ -0,0 +1,572 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::Duration;
+
+use crate::geograph::cost::TotalCost;
+use crate::geograph::route::Query;
+use crate::geograph::{
+    Distance, Geograph, Geoloc, Geolocalizable, Limit, Node, NodeId, PathType, Route, RouteOptions,
+};
+
+/// Index of a mode and id of one of the nodes of its geograph.
+type ModeNode = (usize, NodeId);
+
+/// Id of the virtual nodes at the origin and destination of a route,
+/// used to evaluate the cost of reaching the geographs.
+const ACCESS_NODE: NodeId = NodeId::MIN;
+
+struct Mode<'a> {
+    name: String,
+    geograph: &'a Geograph,
+    options: RouteOptions<'a>,
+    access: bool,
+}
+
+/// Combines the geographs of several transport modes, such as highways,
+/// railways and maritime, into a single network connected at transfer points,
+/// such as ports and rail terminals.
+///
+/// Each mode has its own route options, so their costs can differ: the cost
+/// of a multimodal route is the sum of the costs of its legs in their modes,
+/// of the penalties of its transfers, and of reaching the geographs from the
+/// origin and destination. Costs should use the same unit across modes, such
+/// as the price set by a `Tariff` for each of them, with `cheapest` routes.
+///
+/// Modes are only connected by the transfers added with `link`,
+/// `add_transfer` or `add_transfers_at_tag`, none are inferred from their
+/// geographs. `freight` adds them at the ports and rail terminals of the
+/// bundled geographs.
+///
+/// Time-dependent routing isn't supported: departure times are ignored.
+#[derive(Default)]
+pub struct Multimodal<'a> {
+    modes: Vec<Mode<'a>>,
+    transfers: HashMap<ModeNode, Vec<(ModeNode, f64)>>,
+}
+
+impl<'a> Multimodal<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a transport mode, routed along the geograph with the given options.
+    pub fn add_mode(
+        &mut self,
+        name: &str,
+        geograph: &'a Geograph,
+        mut options: RouteOptions<'a>,
+    ) -> &mut Self {
+        options.departure_time = None;
+        self.modes.push(Mode {
+            name: name.to_string(),
+            geograph,
+            options,
+            access: true,
+        });
+        self
+    }
+
+    /// Restricts the modes that can leave the origin and reach the destination,
+    /// such as trucks for door-to-door routes. All modes can by default.
+    ///
+    /// Panics if any of the modes is unknown.
+    pub fn access_by(&mut self, modes: &[&str]) -> &mut Self {
+        let modes: Vec<usize> = modes.iter().map(|name| self.mode(name)).collect();
+        for (index, mode) in self.modes.iter_mut().enumerate() {
+            mode.access = modes.contains(&index);
+        }
+        self
+    }
+
+    /// Connects two nodes of different modes, in both directions,
+    /// with the penalty of transferring between them.
+    ///
+    /// Panics if any of the modes is unknown, or if they are the same.
+    pub fn link(&mut self, from: (&str, NodeId), to: (&str, NodeId), penalty: f64) -> &mut Self {
+        let (from, to) = ((self.mode(from.0), from.1), (self.mode(to.0), to.1));
+        assert_ne!(from.0, to.0, "A transfer needs two different modes");
+        self.transfers.entry(from).or_default().push((to, penalty));
+        self.transfers.entry(to).or_default().push((from, penalty));
+        self
+    }
+
+    /// Adds a transfer point at a location, such as a port, connecting
+    /// the closest nodes of each of the modes to each other.
+    ///
+    /// Panics if any of the modes is unknown.
+    pub fn add_transfer(
+        &mut self,
+        location: &impl Geolocalizable,
+        modes: &[&str],
+        penalty: f64,
+    ) -> &mut Self {
+        let nodes: Vec<(&str, NodeId)> = modes
+            .iter()
+            .filter_map(|&name| {
+                let closest = self.modes[self.mode(name)]
+                    .geograph
+                    .closest_where(location, |_| true)?;
+                Some((name, closest.id))
+            })
+            .collect();
+        for (i, &from) in nodes.iter().enumerate() {
+            for &to in &nodes[i + 1..] {
+                self.link(from, to, penalty);
+            }
+        }
+        self
+    }
+
+    /// Adds transfer points at the nodes of a mode with the given tag, such as
+    /// `port`, connecting them to the closest node of each of the other modes
+    /// within the maximum distance.
+    ///
+    /// Panics if any of the modes is unknown.
+    pub fn add_transfers_at_tag(
+        &mut self,
+        mode: &str,
+        tag: &str,
+        to: &[&str],
+        max_distance: Distance,
+        penalty: f64,
+    ) -> &mut Self {
+        let geograph = self.modes[self.mode(mode)].geograph;
+        for id in geograph.iter_tagged_nodes(tag) {
+            let Some(node) = geograph.get(id) else {
+                continue;
+            };
+            for &name in to {
+                let closest = self.modes[self.mode(name)]
+                    .geograph
+                    .closest_where(node, |_| true)
+                    .filter(|closest| closest.haversine(node) <= max_distance.km());
+                if let Some(closest) = closest {
+                    self.link((mode, id), (name, closest.id), penalty);
+                }
+            }
+        }
+        self
+    }
+
+    /// Index of a mode, panicking if it is unknown.
+    fn mode(&self, name: &str) -> usize {
+        self.modes
+            .iter()
+            .position(|mode| mode.name == name)
+            .unwrap_or_else(|| panic!("Unknown mode {name:?}"))
+    }
+
+    /// Calculates the cheapest route between two geolocations across the
+    /// modes, split into legs travelled with a single mode.
+    ///
+    /// The origin and destination are reached from the closest node of each
+    /// mode allowed to access them. Like `Geograph::route`, it falls back to
+    /// a direct route without legs if the destination is not reachable, whose
+    /// path type is `PathType::Aborted` if the search reaches the limits of
+    /// the options of a mode. Nodes settled and distances explored count
+    /// against the limits of their mode, from its node closest to the origin.
+    pub fn route(
+        &self,
+        origin: &impl Geolocalizable,
+        destination: &impl Geolocalizable,
+    ) -> MultimodalRoute {
+        let queries: Vec<Query> = self
+            .modes
+            .iter()
+            .map(|mode| {
+                let mut query = Query::new(mode.geograph, &mode.options);
+                let allowed = |node: &Node| query.allows_node(node);
+                if let Some(closest) = mode.geograph.closest_where(origin, allowed) {
+                    query.set_budget(closest);
+                }
+                query
+            })
+            .collect();
+        let origin_node = Node::new(ACCESS_NODE, origin.geoloc(), []);
+        let destination_node = Node::new(ACCESS_NODE, destination.geoloc(), []);
+
+        let mut queue = BinaryHeap::new();
+        let mut costs: HashMap<ModeNode, TotalCost> = HashMap::new();
+        let mut previous: HashMap<ModeNode, ModeNode> = HashMap::new();
+        let mut exits: HashMap<ModeNode, f64> = HashMap::new();
+
+        // Initialize the queue with the entries of the modes accessing the origin,
+        // and record their exits to the destination
+        for (index, mode) in self
+            .modes
+            .iter()
+            .enumerate()
+            .filter(|(_, mode)| mode.access)
+        {
+            let query = &queries[index];
+            let allowed = |node: &Node| query.allows_node(node);
+            if let Some(entry) = mode.geograph.closest_where(origin, allowed) {
+                if let Some(cost) = query.edge_cost(&origin_node, entry, None, 0.0) {
+                    let state = (index, entry.id);
+                    if costs.get(&state).is_none_or(|best| cost < best.0) {
+                        costs.insert(state, TotalCost(cost));
+                        queue.push(Reverse((TotalCost(cost), state)));
+                    }
+                }
+            }
+            if let Some(exit) = mode.geograph.closest_where(destination, allowed) {
+                if let Some(cost) = query.edge_cost(exit, &destination_node, None, 0.0) {
+                    exits.insert((index, exit.id), cost);
+                }
+            }
+        }
+
+        let mut best: Option<(f64, ModeNode)> = None;
+        while let Some(Reverse((cost, current))) = queue.pop() {
+            // Every route left would cost more than the best one found
+            if best.is_some_and(|(best, _)| cost.0 >= best) {
+                break;
+            }
+            // Skip outdated queue entries for nodes already reached at a lower cost
+            if costs.get(&current).is_some_and(|&best| cost > best) {
+                continue;
+            }
+            if let Some(egress) = exits.get(&current) {
+                if best.is_none_or(|(best, _)| cost.0 + egress < best) {
+                    best = Some((cost.0 + egress, current));
+                }
+            }
+
+            let (index, id) = current;
+            let Some(node) = self.modes[index].geograph.get(id) else {
+                continue;
+            };
+            if let Err(limit) = queries[index].settle(node, cost.0) {
+                return MultimodalRoute::aborted(origin.geoloc(), destination.geoloc(), limit);
+            }
+            let neighbors = node.waypoints.iter().filter_map(|&neighbor_id| {
+                let geograph = self.modes[index].geograph;
+                let neighbor = geograph.get(neighbor_id).expect("Missing neighbor");
+                let edge = geograph.edge_from(node, neighbor_id);
+                let cost = queries[index].edge_cost(node, neighbor, edge, cost.0)?;
+                Some(((index, neighbor_id), cost))
+            });
+            let transfers = self
+                .transfers
+                .get(&current)
+                .into_iter()
+                .flatten()
+                .filter(|((mode, id), _)| {
+                    let geograph = self.modes[*mode].geograph;
+                    geograph
+                        .get(*id)
+                        .is_some_and(|node| queries[*mode].allows_node(node))
+                })
+                .copied();
+
+            for (neighbor, additional_cost) in neighbors.chain(transfers) {
+                let total_cost = TotalCost(cost.0 + additional_cost);
+                let neighbor_cost = *costs.get(&neighbor).unwrap_or(&TotalCost(f64::INFINITY));
+                if total_cost < neighbor_cost {
+                    let (mode, neighbor_id) = neighbor;
+                    let (visitor, geograph) = (queries[mode].visitor(), self.modes[mode].geograph);
+                    if let Some((visitor, to)) = visitor.zip(geograph.get(neighbor_id)) {
+                        visitor.relax(node, to, total_cost.0);
+                    }
+                    costs.insert(neighbor, total_cost);
+                    previous.insert(neighbor, current);
+                    queue.push(Reverse((total_cost, neighbor)));
+                }
+            }
+        }
+
+        let Some((cost, exit)) = best else {
+            return MultimodalRoute::direct(origin.geoloc(), destination.geoloc());
+        };
+        let mut states = vec![exit];
+        while let Some(&prev) = previous.get(states.last().unwrap()) {
+            states.push(prev);
+        }
+        states.reverse();
+        self.route_legs(origin, destination, &states, &costs, &queries, cost)
+    }
+
+    /// Builds the legs of a route from the states found by the search,
+    /// split at every change of mode.
+    fn route_legs(
+        &self,
+        origin: &impl Geolocalizable,
+        destination: &impl Geolocalizable,
+        states: &[ModeNode],
+        costs: &HashMap<ModeNode, TotalCost>,
+        queries: &[Query],
+        cost: f64,
+    ) -> MultimodalRoute {
+        let segments: Vec<&[ModeNode]> = states.chunk_by(|a, b| a.0 == b.0).collect();
+        let mut legs: Vec<ModeLeg> = Vec::new();
+        let mut from = origin.geoloc();
+
+        for (i, segment) in segments.iter().enumerate() {
+            let index = segment[0].0;
+            let mode = &self.modes[index];
+            let labels: Vec<(NodeId, f64)> = segment
+                .iter()
+                .map(|state| (state.1, costs[state].0))
+                .collect();
+            let last = mode.geograph.get(labels[labels.len() - 1].0);
+            let to = match (i + 1 == segments.len(), last) {
+                (false, Some(last)) => last.geoloc(),
+                _ => destination.geoloc(),
+            };
+            let start = labels[0].1;
+            let mut route = mode
+                .geograph
+                .route_via(&from, &to, labels, start, &queries[index]);
+            // Legs to a transfer end at their last node
+            if route.path.len() > 2 && route.path[route.path.len() - 2] == to {
+                route.path.pop();
+            }
+            route.duration = queries[index]
+                .speed_profile()
+                .map(|profile| mode.geograph.route_duration(&route, profile, None));
+            route.price = mode
+                .options
+                .tariff
+                .map(|tariff| tariff.price(mode.geograph, &route));
+            from = to;
+            legs.push(ModeLeg {
+                mode: mode.name.clone(),
+                route,
+            });
+        }
+
+        MultimodalRoute {
+            distance: legs.iter().map(|leg| leg.route.distance).sum(),
+            cost,
+            path_type: PathType::ViaWaypoints,
+            duration: legs.iter().map(|leg| leg.route.duration).sum(),
+            price: legs.iter().map(|leg| leg.route.price).sum(),
+            legs,
+        }
+    }
+}
+
+/// Farthest nodes of the other modes connected to a port, whose node of the
+/// maritime geograph may be far offshore, and to a rail terminal.
+#[cfg(all(feature = "highways", feature = "maritime", feature = "railways"))]
+const PORT_TRANSFER_KM: f64 = 150.0;
+#[cfg(all(feature = "highways", feature = "maritime", feature = "railways"))]
+const TERMINAL_TRANSFER_KM: f64 = 25.0;
+
+#[cfg(all(feature = "highways", feature = "maritime", feature = "railways"))]
+impl Multimodal<'static> {
+    /// Combines the `highways`, `railways` and `maritime` geographs, with
+    /// their default options, accessing the origin and destination by road.
+    ///
+    /// Ships transfer to trucks and trains at the ports of the maritime
+    /// geograph, tagged with `maritime::PORT_TAG`, and trains to trucks at
+    /// the terminals of the railways geograph, tagged with
+    /// `railways::TERMINAL_TAG`. The penalty of each transfer is in the unit
+    /// of the costs, kilometres with the default options.
+    pub fn freight(transfer_penalty: f64) -> Self {
+        let mut multimodal = Self::new();
+        multimodal
+            .add_mode("highways", crate::highways::geograph(), RouteOptions::new())
+            .add_mode("railways", crate::railways::geograph(), RouteOptions::new())
+            .add_mode("maritime", crate::maritime::geograph(), RouteOptions::new())
+            .access_by(&["highways"])
+            .add_transfers_at_tag(
+                "maritime",
+                crate::maritime::PORT_TAG,
+                &["highways", "railways"],
+                Distance::from_km(PORT_TRANSFER_KM),
+                transfer_penalty,
+            )
+            .add_transfers_at_tag(
+                "railways",
+                crate::railways::TERMINAL_TAG,
+                &["highways"],
+                Distance::from_km(TERMINAL_TRANSFER_KM),
+                transfer_penalty,
+            );
+        multimodal
+    }
+}
+
+/// Leg of a multimodal route, travelled with a single mode.
+#[derive(Debug)]
+pub struct ModeLeg {
+    pub mode: String,
+    /// Route of the leg along the geograph of its mode, from the end of the
+    /// previous leg, or the origin for the first one.
+    pub route: Route,
+}
+
+/// Represents the result of a multimodal route calculation.
+#[derive(Debug)]
+pub struct MultimodalRoute {
+    /// Geometric length of the route, including the transfers between modes.
+    pub distance: Distance,
+    /// Cost of the route across the modes, including the transfer penalties.
+    pub cost: f64,
+    pub path_type: PathType,
+    /// Estimated travel time, when all the modes have a speed profile.
+    pub duration: Option<Duration>,
+    /// Price of the route, when all the modes have a `Tariff`.
+    pub price: Option<f64>,
+    pub legs: Vec<ModeLeg>,
+}
+
+impl MultimodalRoute {
+    fn direct(origin: Geoloc, destination: Geoloc) -> Self {
+        let route = Route::direct(origin, destination);
+        Self {
+            distance: route.distance,
+            cost: 0.0,
+            path_type: PathType::Direct,
+            duration: None,
+            price: None,
+            legs: Vec::new(),
+        }
+    }
+
+    /// Builds a direct route without legs, used when the search is aborted
+    /// on reaching the limits of one of the modes.
+    fn aborted(origin: Geoloc, destination: Geoloc, limit: Limit) -> Self {
+        Self {
+            path_type: PathType::Aborted(limit),
+            ..Self::direct(origin, destination)
+        }
+    }
+
+    /// Modes of the legs, in order.
+    pub fn modes(&self) -> Vec<&str> {
+        self.legs.iter().map(|leg| leg.mode.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geograph::{SearchLimits, SettledCounter, Speed, SpeedProfile, Tariff};
+
+    /// A road along the equator, a railway from the first degree to the fourth,
+    /// and a sea route from the fourth to the ninth.
+    fn geographs() -> (Geograph, Geograph, Geograph) {
+        let mut road = Geograph::new("Road");
+        road.add(Node::new(0, (0.0, 0.0), vec![1]))
+            .add(Node::new(1, (0.0, 1.0), vec![0, 2]))
+            .add(Node::new(2, (0.0, 9.0), vec![1, 3]))
+            .add(Node::new(3, (0.0, 10.0), vec![2]))
+            .set_speed_profile(SpeedProfile::new(Speed::from_kmh(60.0)));
+        let mut rail = Geograph::new("Rail");
+        rail.add(Node::new(0, (0.0, 1.0), vec![1]))
+            .add(Node::new(1, (0.0, 4.0), vec![0]))
+            .set_speed_profile(SpeedProfile::new(Speed::from_kmh(50.0)));
+        let mut sea = Geograph::new("Sea");
+        sea.add(Node::new(0, (0.0, 4.0), vec![1]))
+            .add(Node::new(1, (0.0, 9.0), vec![0]))
+            .tag_node(0, "port")
+            .tag_node(1, "port")
+            .set_speed_profile(SpeedProfile::new(Speed::from_knots(14.0)));
+        (road, rail, sea)
+    }
+
+    #[test]
+    fn it_routes_door_to_door() {
+        let (road, rail, sea) = geographs();
+        let tariffs = [
+            Tariff::new(0.0, 3.0),
+            Tariff::new(0.0, 1.0),
+            Tariff::new(0.0, 0.5).with_tag_handling("port", 20.0),
+        ];
+        let options = |tariff| RouteOptions::new().with_tariff(tariff).cheapest();
+        let mut multimodal = Multimodal::new();
+        multimodal
+            .add_mode("road", &road, options(&tariffs[0]))
+            .add_mode("rail", &rail, options(&tariffs[1]))
+            .add_mode("sea", &sea, options(&tariffs[2]))
+            .access_by(&["road"])
+            .add_transfer(&(0.0, 1.0), &["road", "rail"], 10.0)
+            .add_transfers_at_tag(
+                "sea",
+                "port",
+                &["rail", "road"],
+                Distance::from_km(1.0),
+                10.0,
+            );
+
+        let route = multimodal.route(&(0.0, 0.0), &(0.0, 10.0));
+        assert_eq!(route.modes(), vec!["road", "rail", "sea", "road"]);
+        assert_eq!(route.legs[1].route.nodes, vec![0, 1]);
+        assert_eq!(route.legs[3].route.nodes, vec![2, 3]);
+        assert_eq!(route.path_type, PathType::ViaWaypoints);
+
+        let km = (0.0, 0.0).haversine(&(0.0, 1.0));
+        assert!((route.distance.km() - 10.0 * km).abs() < 1e-6);
+        let legs_cost: f64 = route.legs.iter().map(|leg| leg.route.cost).sum();
+        assert!((route.cost - legs_cost - 30.0).abs() < 1e-6);
+        // Port handling is charged on the price of the sea leg, not its cost
+        let price = route.price.unwrap();
+        assert!((price - legs_cost - 40.0).abs() < 1e-6);
+        assert!(route.duration.is_some());
+    }
+
+    #[test]
+    fn it_weighs_transfer_penalties() {
+        let (road, rail, sea) = geographs();
+        let mut multimodal = Multimodal::new();
+        multimodal
+            .add_mode("road", &road, RouteOptions::new())
+            .add_mode("rail", &rail, RouteOptions::new())
+            .add_mode("sea", &sea, RouteOptions::new())
+            .access_by(&["road"])
+            .link(("road", 1), ("rail", 0), 1_000.0)
+            .link(("rail", 1), ("sea", 0), 1_000.0)
+            .link(("sea", 1), ("road", 2), 1_000.0);
+
+        let route = multimodal.route(&(0.0, 0.0), &(0.0, 10.0));
+        assert_eq!(route.modes(), vec!["road"]);
+        assert_eq!(route.legs[0].route.nodes, vec![0, 1, 2, 3]);
+        assert_eq!(route.price, None);
+    }
+
+    #[test]
+    fn it_falls_back_to_direct_routes() {
+        let mut rail = Geograph::new("Rail");
+        rail.add(Node::new(0, (0.0, 1.0), vec![]))
+            .add(Node::new(1, (0.0, 4.0), vec![]));
+        let mut multimodal = Multimodal::new();
+        multimodal.add_mode("rail", &rail, RouteOptions::new());
+
+        let route = multimodal.route(&(0.0, 1.0), &(0.0, 4.0));
+        assert_eq!(route.path_type, PathType::Direct);
+        assert!(route.legs.is_empty());
+        assert_eq!(
+            route.distance,
+            Distance::from_km((0.0, 1.0).haversine(&(0.0, 4.0)))
+        );
+    }
+
+    #[test]
+    fn it_aborts_on_the_limits_of_a_mode() {
+        let (road, rail, sea) = geographs();
+        let counter = SettledCounter::new();
+        let limits = SearchLimits::new().with_max_settled(1);
+        let mut multimodal = Multimodal::new();
+        multimodal
+            .add_mode("road", &road, RouteOptions::new().with_visitor(&counter))
+            .add_mode("rail", &rail, RouteOptions::new().with_limits(limits))
+            .add_mode("sea", &sea, RouteOptions::new())
+            .access_by(&["road"])
+            .link(("road", 1), ("rail", 0), 0.0)
+            .link(("rail", 1), ("sea", 0), 0.0)
+            .link(("sea", 1), ("road", 2), 0.0);
+
+        let route = multimodal.route(&(0.0, 0.0), &(0.0, 10.0));
+        assert_eq!(route.path_type, PathType::Aborted(Limit::MaxSettled));
+        assert!(route.legs.is_empty());
+        assert!(counter.settled() > 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown mode")]
+    fn it_rejects_unknown_modes() {
+        Multimodal::new().access_by(&["air"]);
+    }
+}
@@ -1,8 +1,18 @@
+mod terminal;
+
 use crate::{build_geograph_mod, json, prelude::*};
 use lazy_static::lazy_static;
 use routrs_railways_dataset;
 
-build_geograph_mod!(serde_json::from_str::<json::JsonGeograph>(
-    &routrs_railways_dataset::RAILWAYS_JSON
-)
-.expect("Failed to parse Railways Geograph JSON"));
+pub use terminal::{TERMINALS, TERMINAL_TAG};
+
+build_geograph_mod!({
+    let mut geograph: Geograph =
+        serde_json::from_str::<json::JsonGeograph>(&routrs_railways_dataset::RAILWAYS_JSON)
+            .expect("Failed to parse Railways Geograph JSON")
+            .into();
+    terminal::tag_terminals(&mut geograph);
+    // Average speed of a freight train, including stops
+    geograph.set_speed_profile(SpeedProfile::new(Speed::from_kmh(50.0)));
+    geograph
+});
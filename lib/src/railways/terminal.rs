@@ -0,0 +1,49 @@
+use crate::prelude::*;
+
+/// Tag of the nodes of the railways geograph serving a freight terminal.
+pub const TERMINAL_TAG: &str = "rail_terminal";
+
+/// Farthest node from a terminal serving it.
+const TERMINAL_MAX_DISTANCE_KM: f64 = 25.0;
+
+/// Freight rail terminals, at the ports of the maritime geograph and at
+/// inland hubs, served by the node of the railways geograph closest to them.
+pub const TERMINALS: [(&str, Geoloc); 24] = [
+    ("Rotterdam", (51.95, 4.05)),
+    ("Antwerp", (51.28, 4.33)),
+    ("Hamburg", (53.54, 9.97)),
+    ("Le Havre", (49.48, 0.12)),
+    ("Felixstowe", (51.95, 1.31)),
+    ("Gdansk", (54.4, 18.66)),
+    ("Valencia", (39.44, -0.32)),
+    ("Barcelona", (41.35, 2.16)),
+    ("Marseille", (43.35, 5.3)),
+    ("Genoa", (44.4, 8.9)),
+    ("Duisburg", (51.45, 6.73)),
+    ("Madrid", (40.37, -3.66)),
+    ("Lyon", (45.72, 4.86)),
+    ("Milan", (45.49, 9.09)),
+    ("Busan", (35.1, 129.04)),
+    ("Durban", (-29.87, 31.03)),
+    ("Santos", (-23.97, -46.3)),
+    ("New York", (40.67, -74.15)),
+    ("Savannah", (32.08, -81.09)),
+    ("Houston", (29.73, -95.27)),
+    ("Los Angeles", (33.74, -118.26)),
+    ("Chicago", (41.8, -87.75)),
+    ("Kansas City", (39.1, -94.6)),
+    ("Memphis", (35.1, -90.0)),
+];
+
+/// Tags the node closest to each terminal with `TERMINAL_TAG`, if close enough.
+pub(crate) fn tag_terminals(geograph: &mut Geograph) {
+    for (_, geoloc) in TERMINALS {
+        let closest = geograph
+            .closest_where(&geoloc, |_| true)
+            .filter(|node| node.haversine(&geoloc) <= TERMINAL_MAX_DISTANCE_KM);
+        if let Some(node) = closest {
+            let id = node.id;
+            geograph.tag_node(id, TERMINAL_TAG);
+        }
+    }
+}